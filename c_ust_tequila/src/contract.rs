@@ -3,7 +3,7 @@ use cosmwasm_std::{
     MigrateResponse, Querier, StdError, StdResult, Storage, Uint128,
 };
 use cw2::{get_contract_version, set_contract_version};
-use cw20::{BalanceResponse, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
+use cw20::{BalanceResponse, Cw20ReceiveMsg, Expiration, MinterResponse, TokenInfoResponse};
 
 use crate::allowances::{
     handle_burn_from, handle_decrease_allowance, handle_increase_allowance, handle_send_from,
@@ -11,8 +11,12 @@ use crate::allowances::{
 };
 use crate::enumerable::{query_all_accounts, query_all_allowances};
 use crate::migrations::migrate_v01_to_v02;
-use crate::msg::{HandleMsg, InitMsg, InitialBalance, MigrateMsg, QueryMsg};
-use crate::state::{balances, balances_read, token_info, token_info_read, MinterData, TokenInfo,store_config,read_config, Config};
+use crate::msg::{HandleMsg, InitMsg, InitialBalance, MigrateMsg, PendingOwnerResponse, QueryMsg};
+use crate::state::{
+    balances, balances_read, may_read_config, read_config, read_pending_owner,
+    remove_pending_owner, store_config, store_pending_owner, token_info, token_info_read, Config,
+    MinterData, PendingOwner, TokenInfo,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-base";
@@ -20,7 +24,7 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
     set_contract_version(&mut deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -52,6 +56,14 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         mint,
     };
     token_info(&mut deps.storage).save(&data)?;
+
+    // the instantiator owns the contract
+    store_config(
+        &mut deps.storage,
+        &Config {
+            owner_addr: deps.api.canonical_address(&env.message.sender)?,
+        },
+    )?;
     Ok(InitResponse::default())
 }
 
@@ -105,6 +117,12 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             amount,
             msg,
         } => handle_send_from(deps, env, owner, contract, amount, msg),
+        HandleMsg::ProposeNewOwner {
+            owner_addr,
+            expires,
+        } => handle_propose_new_owner(deps, env, owner_addr, expires),
+        HandleMsg::CancelOwnershipProposal {} => handle_cancel_ownership_proposal(deps, env),
+        HandleMsg::AcceptOwnership {} => handle_accept_ownership(deps, env),
     }
 }
 
@@ -133,26 +151,97 @@ pub fn register_minter<S: Storage, A: Api, Q: Querier>(
     Ok(HandleResponse::default())
 }
 
-pub fn update_owner<S: Storage, A: Api, Q: Querier>(
+pub fn handle_propose_new_owner<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    owner_addr: HumanAddr,
+    expires: Option<Expiration>,
+) -> StdResult<HandleResponse> {
+    let config: Config = read_config(&deps.storage)?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Ownership proposal expired"));
+    }
+
+    store_pending_owner(
+        &mut deps.storage,
+        &PendingOwner {
+            owner_addr: deps.api.canonical_address(&owner_addr)?,
+            expires,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "propose_new_owner"),
+            log("owner_addr", owner_addr),
+        ],
+        data: None,
+    })
+}
+
+pub fn handle_cancel_ownership_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    owner_addr: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
-    let mut config: Config = read_config(&deps.storage)?;
+    let config: Config = read_config(&deps.storage)?;
 
     // permission check
     if deps.api.canonical_address(&env.message.sender)? != config.owner_addr {
         return Err(StdError::unauthorized());
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.canonical_address(&owner_addr)?;
+    if read_pending_owner(&deps.storage)?.is_none() {
+        return Err(StdError::generic_err("No ownership proposal pending"));
+    }
+    remove_pending_owner(&mut deps.storage);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("action", "cancel_ownership_proposal")],
+        data: None,
+    })
+}
+
+pub fn handle_accept_ownership<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let pending_owner = match read_pending_owner(&deps.storage)? {
+        Some(p) => p,
+        None => return Err(StdError::generic_err("No ownership proposal pending")),
+    };
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != pending_owner.owner_addr {
+        return Err(StdError::unauthorized());
+    }
+
+    if pending_owner.expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Ownership proposal expired"));
     }
 
-    store_config(&mut deps.storage, &config)?;
+    store_config(
+        &mut deps.storage,
+        &Config {
+            owner_addr: pending_owner.owner_addr,
+        },
+    )?;
+    remove_pending_owner(&mut deps.storage);
+
     Ok(HandleResponse {
         messages: vec![],
-        log: vec![log("action", "update_owner")],
+        log: vec![
+            log("action", "accept_ownership"),
+            log("owner_addr", env.message.sender),
+        ],
         data: None,
     })
 }
@@ -320,6 +409,7 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         QueryMsg::AllAccounts { start_after, limit } => {
             to_binary(&query_all_accounts(deps, start_after, limit)?)
         }
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
     }
 }
 
@@ -361,9 +451,22 @@ pub fn query_minter<S: Storage, A: Api, Q: Querier>(
     Ok(minter)
 }
 
+pub fn query_pending_owner<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<Option<PendingOwnerResponse>> {
+    let pending_owner = match read_pending_owner(&deps.storage)? {
+        Some(p) => Some(PendingOwnerResponse {
+            owner_addr: deps.api.human_address(&p.owner_addr)?,
+            expires: p.expires,
+        }),
+        None => None,
+    };
+    Ok(pending_owner)
+}
+
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     _msg: MigrateMsg,
 ) -> StdResult<MigrateResponse> {
     let old_version = get_contract_version(&deps.storage)?;
@@ -377,13 +480,26 @@ pub fn migrate<S: Storage, A: Api, Q: Querier>(
     // more recent versions do not have the v prefix
     if old_version.version.starts_with("v0.1.") {
         migrate_v01_to_v02(&mut deps.storage)?;
-        set_contract_version(&mut deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-        return Ok(MigrateResponse::default());
+    } else if !old_version.version.starts_with("0.2.") {
+        return Err(StdError::generic_err(format!(
+            "Unknown version {}",
+            old_version.version
+        )));
+    }
+
+    // instances deployed before the owner was stored have no config,
+    // the admin running the migration becomes the owner
+    if may_read_config(&deps.storage)?.is_none() {
+        store_config(
+            &mut deps.storage,
+            &Config {
+                owner_addr: deps.api.canonical_address(&env.message.sender)?,
+            },
+        )?;
     }
-    Err(StdError::generic_err(format!(
-        "Unknown version {}",
-        old_version.version
-    )))
+
+    set_contract_version(&mut deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(MigrateResponse::default())
 }
 
 #[cfg(test)]
@@ -649,6 +765,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn two_step_ownership_transfer() {
+        let mut deps = mock_dependencies(CANONICAL_LENGTH, &[]);
+        do_init(&mut deps, &HumanAddr::from("genesis"), Uint128(1234));
+
+        // nothing to accept yet
+        let env = mock_env(&HumanAddr::from("new_owner"), &[]);
+        let res = handle(&mut deps, env, HandleMsg::AcceptOwnership {});
+        match res.unwrap_err() {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(msg, "No ownership proposal pending".to_string())
+            }
+            e => panic!("Unexpected error: {}", e),
+        }
+
+        let env = mock_env(&HumanAddr::from("creator"), &[]);
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        let msg = HandleMsg::ProposeNewOwner {
+            owner_addr: HumanAddr::from("new_owner"),
+            expires: Some(expires),
+        };
+
+        // only the owner can propose
+        let env = mock_env(&HumanAddr::from("anyone else"), &[]);
+        let res = handle(&mut deps, env, msg.clone());
+        match res.unwrap_err() {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("expected unauthorized error, got {}", e),
+        }
+
+        let env = mock_env(&HumanAddr::from("creator"), &[]);
+        handle(&mut deps, env, msg).unwrap();
+        assert_eq!(
+            query_pending_owner(&deps).unwrap(),
+            Some(PendingOwnerResponse {
+                owner_addr: HumanAddr::from("new_owner"),
+                expires: Expiration::AtHeight(12345 + 10),
+            })
+        );
+
+        // only the proposed owner can accept
+        let env = mock_env(&HumanAddr::from("anyone else"), &[]);
+        let res = handle(&mut deps, env, HandleMsg::AcceptOwnership {});
+        match res.unwrap_err() {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("expected unauthorized error, got {}", e),
+        }
+
+        // not after the proposal expired
+        let mut env = mock_env(&HumanAddr::from("new_owner"), &[]);
+        env.block.height += 10;
+        let res = handle(&mut deps, env, HandleMsg::AcceptOwnership {});
+        match res.unwrap_err() {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(msg, "Ownership proposal expired".to_string())
+            }
+            e => panic!("Unexpected error: {}", e),
+        }
+
+        let env = mock_env(&HumanAddr::from("new_owner"), &[]);
+        handle(&mut deps, env, HandleMsg::AcceptOwnership {}).unwrap();
+        assert_eq!(
+            read_config(&deps.storage).unwrap().owner_addr,
+            deps.api
+                .canonical_address(&HumanAddr::from("new_owner"))
+                .unwrap()
+        );
+        assert_eq!(query_pending_owner(&deps).unwrap(), None);
+
+        // the previous owner lost its rights
+        let msg = HandleMsg::ProposeNewOwner {
+            owner_addr: HumanAddr::from("creator"),
+            expires: None,
+        };
+        let env = mock_env(&HumanAddr::from("creator"), &[]);
+        let res = handle(&mut deps, env, msg);
+        match res.unwrap_err() {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("expected unauthorized error, got {}", e),
+        }
+    }
+
+    #[test]
+    fn cancel_ownership_proposal() {
+        let mut deps = mock_dependencies(CANONICAL_LENGTH, &[]);
+        do_init(&mut deps, &HumanAddr::from("genesis"), Uint128(1234));
+
+        let msg = HandleMsg::ProposeNewOwner {
+            owner_addr: HumanAddr::from("new_owner"),
+            expires: None,
+        };
+        let env = mock_env(&HumanAddr::from("creator"), &[]);
+        handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env(&HumanAddr::from("new_owner"), &[]);
+        let res = handle(&mut deps, env, HandleMsg::CancelOwnershipProposal {});
+        match res.unwrap_err() {
+            StdError::Unauthorized { .. } => {}
+            e => panic!("expected unauthorized error, got {}", e),
+        }
+
+        let env = mock_env(&HumanAddr::from("creator"), &[]);
+        handle(&mut deps, env, HandleMsg::CancelOwnershipProposal {}).unwrap();
+        assert_eq!(query_pending_owner(&deps).unwrap(), None);
+
+        let env = mock_env(&HumanAddr::from("new_owner"), &[]);
+        let res = handle(&mut deps, env, HandleMsg::AcceptOwnership {});
+        match res.unwrap_err() {
+            StdError::GenericErr { msg, .. } => {
+                assert_eq!(msg, "No ownership proposal pending".to_string())
+            }
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+
     #[test]
     fn init_multiple_accounts() {
         let mut deps = mock_dependencies(CANONICAL_LENGTH, &[]);
@@ -964,4 +1195,40 @@ mod tests {
         };
         assert_eq!(allow, expect);
     }
+
+    #[test]
+    fn migrate_seeds_missing_owner() {
+        let mut deps = mock_dependencies(CANONICAL_LENGTH, &[]);
+        // an instance deployed before the owner was stored
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.2.1").unwrap();
+        assert_eq!(may_read_config(&deps.storage).unwrap(), None);
+
+        let env = mock_env(HumanAddr::from("admin"), &[]);
+        migrate(&mut deps, env, MigrateMsg {}).unwrap();
+        let admin = deps
+            .api
+            .canonical_address(&HumanAddr::from("admin"))
+            .unwrap();
+        assert_eq!(read_config(&deps.storage).unwrap().owner_addr, admin);
+
+        // the seeded owner can hand over ownership
+        let env = mock_env(HumanAddr::from("admin"), &[]);
+        let msg = HandleMsg::ProposeNewOwner {
+            owner_addr: HumanAddr::from("new_owner"),
+            expires: None,
+        };
+        handle(&mut deps, env, msg).unwrap();
+
+        // a stored owner is kept on later migrations
+        let env = mock_env(HumanAddr::from("someone else"), &[]);
+        migrate(&mut deps, env, MigrateMsg {}).unwrap();
+        assert_eq!(read_config(&deps.storage).unwrap().owner_addr, admin);
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.3.0").unwrap();
+        let env = mock_env(HumanAddr::from("admin"), &[]);
+        match migrate(&mut deps, env, MigrateMsg {}).unwrap_err() {
+            StdError::GenericErr { msg, .. } => assert_eq!(msg, "Unknown version 0.3.0"),
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
 }
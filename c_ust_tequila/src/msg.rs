@@ -120,6 +120,15 @@ pub enum HandleMsg {
     },
     /// Only with "approval" extension. Destroys tokens forever
     BurnFrom { owner: HumanAddr, amount: Uint128 },
+    /// Only the owner. Proposes a new owner, who has to accept before the optional expiry.
+    ProposeNewOwner {
+        owner_addr: HumanAddr,
+        expires: Option<Expiration>,
+    },
+    /// Only the owner. Withdraws the pending ownership proposal.
+    CancelOwnershipProposal {},
+    /// Only the proposed owner. Accepts the pending ownership proposal.
+    AcceptOwnership {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -157,6 +166,15 @@ pub enum QueryMsg {
         start_after: Option<HumanAddr>,
         limit: Option<u32>,
     },
+    /// Returns the proposed owner and the proposal expiry, if any.
+    /// Return type: Option<PendingOwnerResponse>.
+    PendingOwner {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingOwnerResponse {
+    pub owner_addr: HumanAddr,
+    pub expires: Expiration,
 }
 
 /// We currently take no arguments for migrations
@@ -6,7 +6,7 @@ use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket,
     ReadonlyPrefixedStorage, ReadonlySingleton, Singleton,
 };
-use cw20::AllowanceResponse;
+use cw20::{AllowanceResponse, Expiration};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -36,6 +36,7 @@ const TOKEN_INFO_KEY: &[u8] = b"token_info";
 const PREFIX_BALANCE: &[u8] = b"balance";
 const PREFIX_ALLOWANCE: &[u8] = b"allowance";
 pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_PENDING_OWNER: &[u8] = b"pending_owner";
 
 // meta is the token definition as well as the total_supply
 pub fn token_info<S: Storage>(storage: &mut S) -> Singleton<S, TokenInfo> {
@@ -88,4 +89,27 @@ pub fn store_config<S: Storage>(storage: &mut S, data: &Config) -> StdResult<()>
 
 pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
+}
+
+/// Config is missing on instances deployed before the owner was stored
+pub fn may_read_config<S: Storage>(storage: &S) -> StdResult<Option<Config>> {
+    ReadonlySingleton::new(storage, KEY_CONFIG).may_load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner_addr: CanonicalAddr,
+    pub expires: Expiration,
+}
+
+pub fn store_pending_owner<S: Storage>(storage: &mut S, data: &PendingOwner) -> StdResult<()> {
+    Singleton::new(storage, KEY_PENDING_OWNER).save(data)
+}
+
+pub fn read_pending_owner<S: Storage>(storage: &S) -> StdResult<Option<PendingOwner>> {
+    ReadonlySingleton::new(storage, KEY_PENDING_OWNER).may_load()
+}
+
+pub fn remove_pending_owner<S: Storage>(storage: &mut S) {
+    Singleton::<S, PendingOwner>::new(storage, KEY_PENDING_OWNER).remove()
 }
\ No newline at end of file
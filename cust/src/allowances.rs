@@ -4,8 +4,9 @@ use cosmwasm_std::{
 };
 use cw20::{AllowanceResponse, Cw20ReceiveMsg, Expiration};
 
+use crate::contract::accounting_hook_msgs;
 use crate::error::ContractError;
-use crate::state::{ALLOWANCES, BALANCES, TOKEN_INFO};
+use crate::state::{decrease_balance, increase_balance, ALLOWANCES, TOKEN_INFO, TOTAL_SUPPLY};
 
 pub fn execute_increase_allowance(
     deps: DepsMut,
@@ -122,26 +123,23 @@ pub fn execute_transfer_from(
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
-
-    let res = Response::new().add_attributes(vec![
-        attr("action", "transfer_from"),
-        attr("from", owner),
-        attr("to", recipient),
-        attr("by", info.sender),
-        attr("amount", amount),
-    ]);
+    decrease_balance(deps.storage, &owner_addr, env.block.height, amount)?;
+    increase_balance(deps.storage, &rcpt_addr, env.block.height, amount)?;
+
+    let res = Response::new()
+        .add_messages(accounting_hook_msgs(
+            deps.storage,
+            &owner_addr,
+            &rcpt_addr,
+            amount,
+        )?)
+        .add_attributes(vec![
+            attr("action", "transfer_from"),
+            attr("from", owner),
+            attr("to", recipient),
+            attr("by", info.sender),
+            attr("amount", amount),
+        ]);
     Ok(res)
 }
 
@@ -159,18 +157,13 @@ pub fn execute_burn_from(
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
+    decrease_balance(deps.storage, &owner_addr, env.block.height, amount)?;
     // reduce total_supply
-    TOKEN_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
+    let meta = TOKEN_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
         meta.total_supply = meta.total_supply.checked_sub(amount)?;
         Ok(meta)
     })?;
+    TOTAL_SUPPLY.save(deps.storage, &meta.total_supply, env.block.height)?;
 
     let res = Response::new().add_attributes(vec![
         attr("action", "burn_from"),
@@ -197,18 +190,8 @@ pub fn execute_send_from(
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
+    decrease_balance(deps.storage, &owner_addr, env.block.height, amount)?;
+    increase_balance(deps.storage, &rcpt_addr, env.block.height, amount)?;
 
     let attrs = vec![
         attr("action", "send_from"),
@@ -226,7 +209,15 @@ pub fn execute_send_from(
     }
     .into_cosmos_msg(contract)?;
 
-    let res = Response::new().add_message(msg).add_attributes(attrs);
+    let res = Response::new()
+        .add_messages(accounting_hook_msgs(
+            deps.storage,
+            &owner_addr,
+            &rcpt_addr,
+            amount,
+        )?)
+        .add_message(msg)
+        .add_attributes(attrs);
     Ok(res)
 }
 
@@ -247,13 +238,28 @@ mod tests {
     use cosmwasm_std::{coins, CosmosMsg, SubMsg, Timestamp, WasmMsg};
     use cw20::{Cw20Coin, TokenInfoResponse};
 
-    use crate::contract::{execute, instantiate, query_balance, query_token_info};
+    use crate::contract::{
+        execute, instantiate, query_balance, query_token_info, query_token_stats,
+    };
     use crate::msg::{ExecuteMsg, InstantiateMsg};
 
     fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
         query_balance(deps, address.into()).unwrap().balance
     }
 
+    fn holder_count(deps: Deps) -> u64 {
+        query_token_stats(deps).unwrap().holder_count
+    }
+
+    fn allow(deps: DepsMut, owner: &str, spender: &str, amount: Uint128) {
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.to_string(),
+            amount,
+            expires: None,
+        };
+        execute(deps, mock_env(), mock_info(owner, &[]), msg).unwrap();
+    }
+
     // this will set up the instantiation for other tests
     fn do_instantiate<T: Into<String>>(
         mut deps: DepsMut,
@@ -741,4 +747,123 @@ mod tests {
         let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(err, ContractError::Expired {});
     }
+
+    #[test]
+    fn transfer_from_counts_holders() {
+        let mut deps = mock_dependencies(&[]);
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let rcpt = String::from("addr0003");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        allow(deps.as_mut(), &owner, &spender, Uint128::new(1000));
+        assert_eq!(holder_count(deps.as_ref()), 1);
+
+        // the recipient becomes a holder
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: rcpt.clone(),
+            amount: Uint128::new(400),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 2);
+
+        // the owner drops to zero
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: rcpt.clone(),
+            amount: Uint128::new(600),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::zero());
+        assert_eq!(holder_count(deps.as_ref()), 1);
+
+        // and comes back from zero
+        allow(deps.as_mut(), &rcpt, &spender, Uint128::new(100));
+        let msg = ExecuteMsg::TransferFrom {
+            owner: rcpt.clone(),
+            recipient: owner.clone(),
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 2);
+    }
+
+    #[test]
+    fn send_from_counts_holders() {
+        let mut deps = mock_dependencies(&[]);
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let contract = String::from("cool-dex");
+        let send_msg = Binary::from(r#"{"some":123}"#.as_bytes());
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        allow(deps.as_mut(), &owner, &spender, Uint128::new(1000));
+
+        // the contract becomes a holder
+        let msg = ExecuteMsg::SendFrom {
+            owner: owner.clone(),
+            contract: contract.clone(),
+            amount: Uint128::new(400),
+            msg: send_msg.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 2);
+
+        // the owner drops to zero
+        let msg = ExecuteMsg::SendFrom {
+            owner: owner.clone(),
+            contract: contract.clone(),
+            amount: Uint128::new(600),
+            msg: send_msg.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::zero());
+        assert_eq!(holder_count(deps.as_ref()), 1);
+
+        // and comes back from zero, while the contract drops to zero
+        allow(deps.as_mut(), &contract, &spender, Uint128::new(1000));
+        let msg = ExecuteMsg::SendFrom {
+            owner: contract.clone(),
+            contract: owner.clone(),
+            amount: Uint128::new(1000),
+            msg: send_msg,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::zero());
+        assert_eq!(holder_count(deps.as_ref()), 1);
+    }
+
+    #[test]
+    fn burn_from_counts_holders() {
+        let mut deps = mock_dependencies(&[]);
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        allow(deps.as_mut(), &owner, &spender, Uint128::new(1000));
+
+        // a partial burn keeps the holder
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(400),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 1);
+
+        // burning the rest removes it
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(600),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::zero());
+        assert_eq!(holder_count(deps.as_ref()), 0);
+
+        // a failed burn from an empty balance changes nothing
+        allow(deps.as_mut(), &owner, &spender, Uint128::new(1));
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(1),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap_err();
+        assert_eq!(holder_count(deps.as_ref()), 0);
+    }
 }
@@ -1,7 +1,8 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128,
 };
 
 use cw2::set_contract_version;
@@ -14,10 +15,17 @@ use crate::allowances::{
     execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
     execute_transfer_from, query_allowance,
 };
-use crate::enumerable::{query_all_accounts, query_all_allowances};
+use crate::enumerable::{query_all_accounts, query_all_allowances, query_minters};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg};
-use crate::state::{MinterData, TokenInfo, BALANCES, LOGO, MARKETING_INFO, TOKEN_INFO};
+use crate::msg::{
+    AccountingHookMsg, AccountingHookResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    OwnerResponse, QueryMsg, TokenStatsResponse, TotalSupplyResponse,
+};
+use crate::state::{
+    count_holders, decrease_balance, increase_balance, start_holder_recount, update_balance,
+    MinterData, TokenInfo, ACCOUNTING_HOOK, BALANCES, HOLDER_COUNT, LOGO, MARKETING_INFO, MINTERS,
+    OWNER, SNAPSHOT_START, TOKEN_INFO, TOTAL_SUPPLY,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-base";
@@ -25,6 +33,10 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const LOGO_SIZE_CAP: usize = 5 * 1024;
 
+// accounts a holder recount goes through per call
+const DEFAULT_COUNT_LIMIT: u32 = 100;
+const MAX_COUNT_LIMIT: u32 = 500;
+
 /// Checks if data starts with XML preamble
 fn verify_xml_preamble(data: &[u8]) -> Result<(), ContractError> {
     // The easiest way to perform this check would be just match on regex, however regex
@@ -89,15 +101,17 @@ fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     // check valid token info
     msg.validate()?;
+    OWNER.save(deps.storage, &info.sender)?;
+    HOLDER_COUNT.save(deps.storage, &0)?;
     // create initial accounts
-    let total_supply = create_accounts(&mut deps, &msg.initial_balances)?;
+    let total_supply = create_accounts(&mut deps, &msg.initial_balances, env.block.height)?;
 
     if let Some(limit) = msg.get_cap() {
         if total_supply > limit {
@@ -122,6 +136,8 @@ pub fn instantiate(
         mint,
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
+    SNAPSHOT_START.save(deps.storage, &env.block.height)?;
 
     if let Some(marketing) = msg.marketing {
         let logo = if let Some(logo) = marketing.logo {
@@ -151,11 +167,15 @@ pub fn instantiate(
     Ok(Response::default())
 }
 
-pub fn create_accounts(deps: &mut DepsMut, accounts: &[Cw20Coin]) -> StdResult<Uint128> {
+pub fn create_accounts(
+    deps: &mut DepsMut,
+    accounts: &[Cw20Coin],
+    height: u64,
+) -> StdResult<Uint128> {
     let mut total_supply = Uint128::zero();
     for row in accounts {
         let address = deps.api.addr_validate(&row.address)?;
-        BALANCES.save(deps.storage, &address, &row.amount)?;
+        update_balance(deps.storage, &address, height, |_| Ok(row.amount))?;
         total_supply += row.amount;
     }
     Ok(total_supply)
@@ -207,12 +227,44 @@ pub fn execute(
             marketing,
         } => execute_update_marketing(deps, env, info, project, description, marketing),
         ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
+        ExecuteMsg::UpdateAccountingHook { contract } => {
+            execute_update_accounting_hook(deps, env, info, contract)
+        }
+        ExecuteMsg::UpdateMinter { new_minter, cap } => {
+            execute_update_minter(deps, env, info, new_minter, cap)
+        }
+        ExecuteMsg::UpdateOwner { new_owner } => execute_update_owner(deps, env, info, new_owner),
+        ExecuteMsg::AddMinter { minter, cap } => execute_add_minter(deps, env, info, minter, cap),
+        ExecuteMsg::RemoveMinter { minter } => execute_remove_minter(deps, env, info, minter),
+        ExecuteMsg::SetMinterCap { minter, cap } => {
+            execute_set_minter_cap(deps, env, info, minter, cap)
+        }
+        ExecuteMsg::CountHolders { limit } => execute_count_holders(deps, env, info, limit),
+    }
+}
+
+/// Notifies the accounting hook contract, if any, of amount moved from sender to recipient.
+/// Moves to the hook contract itself are notified too, the hook decides what to account for.
+pub fn accounting_hook_msgs(
+    storage: &dyn Storage,
+    sender: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    match ACCOUNTING_HOOK.may_load(storage)? {
+        Some(hook) => Ok(vec![AccountingHookMsg::BalanceMoved {
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+        }
+        .into_cosmos_msg(hook)?]),
+        _ => Ok(vec![]),
     }
 }
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -223,20 +275,16 @@ pub fn execute_transfer(
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
 
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
+    decrease_balance(deps.storage, &info.sender, env.block.height, amount)?;
+    increase_balance(deps.storage, &rcpt_addr, env.block.height, amount)?;
 
     let res = Response::new()
+        .add_messages(accounting_hook_msgs(
+            deps.storage,
+            &info.sender,
+            &rcpt_addr,
+            amount,
+        )?)
         .add_attribute("action", "transfer")
         .add_attribute("from", info.sender)
         .add_attribute("to", recipient)
@@ -246,7 +294,7 @@ pub fn execute_transfer(
 
 pub fn execute_burn(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
@@ -255,18 +303,13 @@ pub fn execute_burn(
     }
 
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
+    decrease_balance(deps.storage, &info.sender, env.block.height, amount)?;
     // reduce total_supply
-    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
+    let meta = TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
         info.total_supply = info.total_supply.checked_sub(amount)?;
         Ok(info)
     })?;
+    TOTAL_SUPPLY.save(deps.storage, &meta.total_supply, env.block.height)?;
 
     let res = Response::new()
         .add_attribute("action", "burn")
@@ -277,7 +320,7 @@ pub fn execute_burn(
 
 pub fn execute_mint(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -287,7 +330,13 @@ pub fn execute_mint(
     }
 
     let mut config = TOKEN_INFO.load(deps.storage)?;
-    if config.mint.is_none() || config.mint.as_ref().unwrap().minter != info.sender {
+    // added minters draw on their own remaining cap
+    if let Some(remaining) = MINTERS.may_load(deps.storage, &info.sender)? {
+        let remaining = remaining
+            .checked_sub(amount)
+            .map_err(|_| ContractError::CannotExceedCap {})?;
+        MINTERS.save(deps.storage, &info.sender, &remaining)?;
+    } else if config.mint.is_none() || config.mint.as_ref().unwrap().minter != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -299,14 +348,11 @@ pub fn execute_mint(
         }
     }
     TOKEN_INFO.save(deps.storage, &config)?;
+    TOTAL_SUPPLY.save(deps.storage, &config.total_supply, env.block.height)?;
 
     // add amount to recipient balance
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
+    increase_balance(deps.storage, &rcpt_addr, env.block.height, amount)?;
 
     let res = Response::new()
         .add_attribute("action", "mint")
@@ -317,7 +363,7 @@ pub fn execute_mint(
 
 pub fn execute_send(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
     amount: Uint128,
@@ -330,20 +376,16 @@ pub fn execute_send(
     let rcpt_addr = deps.api.addr_validate(&contract)?;
 
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        },
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
-    )?;
+    decrease_balance(deps.storage, &info.sender, env.block.height, amount)?;
+    increase_balance(deps.storage, &rcpt_addr, env.block.height, amount)?;
 
     let res = Response::new()
+        .add_messages(accounting_hook_msgs(
+            deps.storage,
+            &info.sender,
+            &rcpt_addr,
+            amount,
+        )?)
         .add_attribute("action", "send")
         .add_attribute("from", &info.sender)
         .add_attribute("to", &contract)
@@ -359,6 +401,159 @@ pub fn execute_send(
     Ok(res)
 }
 
+pub fn execute_update_accounting_hook(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    contract: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    match contract {
+        Some(contract) => {
+            let hook = deps.api.addr_validate(&contract)?;
+            ACCOUNTING_HOOK.save(deps.storage, &hook)?;
+        }
+        None => ACCOUNTING_HOOK.remove(deps.storage),
+    }
+
+    let res = Response::new().add_attribute("action", "update_accounting_hook");
+    Ok(res)
+}
+
+pub fn execute_count_holders(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_COUNT_LIMIT)
+        .clamp(1, MAX_COUNT_LIMIT) as usize;
+    let done = count_holders(deps.storage, limit)?;
+
+    let res = Response::new()
+        .add_attribute("action", "count_holders")
+        .add_attribute("done", done.to_string())
+        .add_attribute("holder_count", HOLDER_COUNT.load(deps.storage)?.to_string());
+    Ok(res)
+}
+
+pub fn execute_update_minter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_minter: String,
+    cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    if let Some(limit) = cap {
+        if config.total_supply > limit {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+    config.mint = Some(MinterData {
+        minter: deps.api.addr_validate(&new_minter)?,
+        cap,
+    });
+    TOKEN_INFO.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_minter")
+        .add_attribute("minter", new_minter);
+    Ok(res)
+}
+
+pub fn execute_update_owner(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    let owner = deps.api.addr_validate(&new_owner)?;
+    OWNER.save(deps.storage, &owner)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_owner")
+        .add_attribute("owner", new_owner);
+    Ok(res)
+}
+
+pub fn execute_add_minter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+    cap: Uint128,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if MINTERS.has(deps.storage, &minter_addr) {
+        return Err(ContractError::MinterAlreadyRegistered {});
+    }
+    MINTERS.save(deps.storage, &minter_addr, &cap)?;
+
+    let res = Response::new()
+        .add_attribute("action", "add_minter")
+        .add_attribute("minter", minter)
+        .add_attribute("cap", cap);
+    Ok(res)
+}
+
+pub fn execute_remove_minter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if !MINTERS.has(deps.storage, &minter_addr) {
+        return Err(ContractError::MinterNotFound {});
+    }
+    MINTERS.remove(deps.storage, &minter_addr);
+
+    let res = Response::new()
+        .add_attribute("action", "remove_minter")
+        .add_attribute("minter", minter);
+    Ok(res)
+}
+
+pub fn execute_set_minter_cap(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+    cap: Uint128,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if !MINTERS.has(deps.storage, &minter_addr) {
+        return Err(ContractError::MinterNotFound {});
+    }
+    MINTERS.save(deps.storage, &minter_addr, &cap)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_minter_cap")
+        .add_attribute("minter", minter)
+        .add_attribute("cap", cap);
+    Ok(res)
+}
+
+fn assert_owner(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    match OWNER.may_load(deps.storage)? {
+        Some(owner) if owner == *sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
 pub fn execute_update_marketing(
     deps: DepsMut,
     _env: Env,
@@ -451,6 +646,12 @@ pub fn execute_upload_logo(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::BalanceAtHeight { address, height } => {
+            to_binary(&query_balance_at_height(deps, address, height)?)
+        }
+        QueryMsg::TotalSupplyAtHeight { height } => {
+            to_binary(&query_total_supply_at_height(deps, height)?)
+        }
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
         QueryMsg::Allowance { owner, spender } => {
@@ -466,6 +667,12 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
         QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::TokenStats {} => to_binary(&query_token_stats(deps)?),
+        QueryMsg::AccountingHook {} => to_binary(&query_accounting_hook(deps)?),
+        QueryMsg::Owner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::Minters { start_after, limit } => {
+            to_binary(&query_minters(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -477,6 +684,39 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
     Ok(BalanceResponse { balance })
 }
 
+/// Fails for heights before the checkpoints started, their values are not known
+fn assert_snapshot_height(deps: Deps, height: u64) -> StdResult<()> {
+    let start = SNAPSHOT_START.load(deps.storage)?;
+    if height < start {
+        return Err(StdError::generic_err(format!(
+            "No snapshots before height {}",
+            start
+        )));
+    }
+    Ok(())
+}
+
+pub fn query_balance_at_height(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    assert_snapshot_height(deps, height)?;
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_total_supply_at_height(deps: Deps, height: u64) -> StdResult<TotalSupplyResponse> {
+    assert_snapshot_height(deps, height)?;
+    let total_supply = TOTAL_SUPPLY
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    Ok(TotalSupplyResponse { total_supply })
+}
+
 pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
     let res = TokenInfoResponse {
@@ -488,6 +728,28 @@ pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     Ok(res)
 }
 
+pub fn query_token_stats(deps: Deps) -> StdResult<TokenStatsResponse> {
+    let info = TOKEN_INFO.load(deps.storage)?;
+    Ok(TokenStatsResponse {
+        total_supply: info.total_supply,
+        holder_count: HOLDER_COUNT.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_accounting_hook(deps: Deps) -> StdResult<AccountingHookResponse> {
+    let contract = ACCOUNTING_HOOK.may_load(deps.storage)?;
+    Ok(AccountingHookResponse {
+        contract: contract.map(|addr| addr.into()),
+    })
+}
+
+pub fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    let owner = OWNER.may_load(deps.storage)?;
+    Ok(OwnerResponse {
+        owner: owner.map(|addr| addr.into()),
+    })
+}
+
 pub fn query_minter(deps: Deps) -> StdResult<Option<MinterResponse>> {
     let meta = TOKEN_INFO.load(deps.storage)?;
     let minter = match meta.mint {
@@ -522,10 +784,10 @@ pub fn query_download_logo(deps: Deps) -> StdResult<DownloadLogoResponse> {
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary, Addr, CosmosMsg, StdError, SubMsg, WasmMsg};
+    use cosmwasm_std::{attr, coins, from_binary, Addr, CosmosMsg, StdError, SubMsg, WasmMsg};
 
     use super::*;
-    use crate::msg::InstantiateMarketingInfo;
+    use crate::msg::{InstantiateMarketingInfo, MinterInfo, MintersResponse};
 
     fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
         query_balance(deps, address.into()).unwrap().balance
@@ -1005,6 +1267,175 @@ mod tests {
         );
     }
 
+    #[test]
+    fn token_stats_counts_holders() {
+        let mut deps = mock_dependencies(&[]);
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let addr3 = String::from("addr0003");
+        let amount = Uint128::from(1000u128);
+        let minter = String::from("minter");
+        do_instantiate_with_minter(deps.as_mut(), &addr1, amount, &minter, None);
+        assert_eq!(
+            query_token_stats(deps.as_ref()).unwrap(),
+            TokenStatsResponse {
+                total_supply: amount,
+                holder_count: 1,
+            }
+        );
+
+        // mint to a new holder
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 2);
+
+        // moving a whole balance keeps the count
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr3.clone(),
+            amount,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 2);
+
+        // a partial transfer to an existing holder keeps the count
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::from(400u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr3, &[]), msg).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 2);
+
+        // burning a whole balance removes the holder
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::from(1400u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            query_token_stats(deps.as_ref()).unwrap(),
+            TokenStatsResponse {
+                total_supply: Uint128::from(600u128),
+                holder_count: 1,
+            }
+        );
+
+        // transfer_from adds the recipient
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: addr1.clone(),
+            amount,
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr3, &[]), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: addr3.clone(),
+            recipient: addr2.clone(),
+            amount: Uint128::from(100u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 2);
+
+        // burn_from empties the owner
+        let msg = ExecuteMsg::BurnFrom {
+            owner: addr3.clone(),
+            amount: Uint128::from(500u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 1);
+
+        // migration recounts the holders
+        HOLDER_COUNT.remove(deps.as_mut().storage);
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 1);
+    }
+
+    #[test]
+    fn holders_recounted_in_pages() {
+        let mut deps = mock_dependencies(&[]);
+        let amount = Uint128::from(1000u128);
+        let minter = String::from("minter");
+        do_instantiate_with_minter(deps.as_mut(), "addr0001", amount, &minter, None);
+        for recipient in ["addr0002", "addr0003", "addr0004", "addr0005"].iter() {
+            let msg = ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+        }
+
+        // nothing to count without a recount in progress
+        let msg = ExecuteMsg::CountHolders { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("done", "true"));
+        assert_eq!(res.attributes[2], attr("holder_count", "5"));
+
+        start_holder_recount(deps.as_mut().storage).unwrap();
+        let msg = ExecuteMsg::CountHolders { limit: Some(2) };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "count_holders"),
+                attr("done", "false"),
+                attr("holder_count", "2"),
+            ]
+        );
+
+        // an account already counted leaves, the ones not reached yet are left alone
+        let transfer = ExecuteMsg::Transfer {
+            recipient: String::from("addr0004"),
+            amount,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0001", &[]),
+            transfer,
+        )
+        .unwrap();
+        let mint = ExecuteMsg::Mint {
+            recipient: String::from("addr0006"),
+            amount,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), mint).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 1);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("done", "false"));
+        assert_eq!(res.attributes[2], attr("holder_count", "3"));
+
+        // a counted account comes back
+        let transfer = ExecuteMsg::Transfer {
+            recipient: String::from("addr0001"),
+            amount,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0004", &[]),
+            transfer,
+        )
+        .unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 4);
+
+        let msg = ExecuteMsg::CountHolders { limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr("done", "true"));
+        assert_eq!(res.attributes[2], attr("holder_count", "6"));
+
+        // the count is kept up to date again
+        let burn = ExecuteMsg::Burn { amount };
+        execute(deps.as_mut(), mock_env(), mock_info("addr0006", &[]), burn).unwrap();
+        assert_eq!(query_token_stats(deps.as_ref()).unwrap().holder_count, 5);
+    }
+
     #[test]
     fn burn() {
         let mut deps = mock_dependencies(&coins(2, "token"));
@@ -1128,6 +1559,606 @@ mod tests {
         );
     }
 
+    #[test]
+    fn accounting_hook() {
+        let mut deps = mock_dependencies(&[]);
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let hook = String::from("vault");
+        let amount = Uint128::from(1000u128);
+        let transfer = Uint128::from(300u128);
+        do_instantiate_with_minter(deps.as_mut(), &addr1, amount, &hook, None);
+
+        // only the owner registers the hook, not the minter
+        let msg = ExecuteMsg::UpdateAccountingHook {
+            contract: Some(hook.clone()),
+        };
+        for sender in [&addr1, &hook].iter() {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                msg.clone(),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            query_accounting_hook(deps.as_ref()).unwrap(),
+            AccountingHookResponse {
+                contract: Some(hook.clone()),
+            }
+        );
+
+        // transfers notify the hook
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: transfer,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hook.clone(),
+                msg: to_binary(&AccountingHookMsg::BalanceMoved {
+                    sender: addr1.clone(),
+                    recipient: addr2.clone(),
+                    amount: transfer,
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+
+        // the hook is notified before the receiving contract
+        let msg = ExecuteMsg::Send {
+            contract: addr1.clone(),
+            amount: transfer,
+            msg: Binary::from(r#"{"some":123}"#.as_bytes()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hook.clone(),
+                msg: to_binary(&AccountingHookMsg::BalanceMoved {
+                    sender: addr2.clone(),
+                    recipient: addr1.clone(),
+                    amount: transfer,
+                })
+                .unwrap(),
+                funds: vec![],
+            }))
+        );
+
+        // moves to the hook contract itself are notified too
+        let msg = ExecuteMsg::Send {
+            contract: hook.clone(),
+            amount: transfer,
+            msg: Binary::from(r#"{"some":123}"#.as_bytes()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        let msg = ExecuteMsg::Transfer {
+            recipient: hook.clone(),
+            amount: transfer,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: hook.clone(),
+                msg: to_binary(&AccountingHookMsg::BalanceMoved {
+                    sender: addr1.clone(),
+                    recipient: hook.clone(),
+                    amount: transfer,
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+
+        // unregistered, nothing is notified
+        let msg = ExecuteMsg::UpdateAccountingHook { contract: None };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: transfer,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+        assert_eq!(res.messages.len(), 0);
+    }
+
+    #[test]
+    fn update_minter_and_owner() {
+        let mut deps = mock_dependencies(&[]);
+        let amount = Uint128::from(1000u128);
+        do_instantiate_with_minter(deps.as_mut(), "addr0001", amount, "minter", None);
+        assert_eq!(
+            query_owner(deps.as_ref()).unwrap(),
+            OwnerResponse {
+                owner: Some(String::from("creator")),
+            }
+        );
+
+        // only the owner replaces the minter
+        let msg = ExecuteMsg::UpdateMinter {
+            new_minter: String::from("vault"),
+            cap: Some(Uint128::from(5000u128)),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // the cap cannot be below the current supply
+        let low_cap = ExecuteMsg::UpdateMinter {
+            new_minter: String::from("vault"),
+            cap: Some(Uint128::from(999u128)),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            low_cap,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            query_minter(deps.as_ref()).unwrap(),
+            Some(MinterResponse {
+                minter: String::from("vault"),
+                cap: Some(Uint128::from(5000u128)),
+            })
+        );
+
+        // the former minter cannot mint anymore
+        let mint = ExecuteMsg::Mint {
+            recipient: String::from("addr0001"),
+            amount,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            mint.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("vault", &[]), mint).unwrap();
+
+        // only the owner hands over the ownership
+        let msg = ExecuteMsg::UpdateOwner {
+            new_owner: String::from("new_owner"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("vault", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            query_owner(deps.as_ref()).unwrap().owner,
+            Some(String::from("new_owner"))
+        );
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // migration can set the owner
+        let msg = MigrateMsg {
+            owner: Some(String::from("creator")),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            query_owner(deps.as_ref()).unwrap().owner,
+            Some(String::from("creator"))
+        );
+    }
+
+    #[test]
+    fn added_minters() {
+        let mut deps = mock_dependencies(&[]);
+        let amount = Uint128::from(1000u128);
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            "addr0001",
+            amount,
+            "minter",
+            Some(Uint128::from(10000u128)),
+        );
+
+        // only the owner manages minters
+        let msg = ExecuteMsg::AddMinter {
+            minter: String::from("vault1"),
+            cap: Uint128::from(500u128),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MinterAlreadyRegistered {});
+
+        let msg = ExecuteMsg::AddMinter {
+            minter: String::from("vault2"),
+            cap: Uint128::from(700u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // an added minter draws on its remaining cap
+        let msg = ExecuteMsg::Mint {
+            recipient: String::from("addr0002"),
+            amount: Uint128::from(400u128),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("vault1", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("vault1", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+        assert_eq!(
+            get_balance(deps.as_ref(), "addr0002"),
+            Uint128::from(400u128)
+        );
+
+        // the instantiation minter is unaffected
+        let msg = ExecuteMsg::Mint {
+            recipient: String::from("addr0002"),
+            amount: Uint128::from(400u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+
+        assert_eq!(
+            query_minters(deps.as_ref(), None, None).unwrap(),
+            MintersResponse {
+                minters: vec![
+                    MinterInfo {
+                        minter: String::from("vault1"),
+                        cap: Uint128::from(100u128),
+                    },
+                    MinterInfo {
+                        minter: String::from("vault2"),
+                        cap: Uint128::from(700u128),
+                    },
+                ],
+            }
+        );
+        let minters = query_minters(deps.as_ref(), Some(String::from("vault1")), Some(1)).unwrap();
+        assert_eq!(minters.minters[0].minter, String::from("vault2"));
+
+        let msg = ExecuteMsg::SetMinterCap {
+            minter: String::from("vault1"),
+            cap: Uint128::from(1000u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Mint {
+            recipient: String::from("addr0002"),
+            amount: Uint128::from(1000u128),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("vault1", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RemoveMinter {
+            minter: String::from("vault1"),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MinterNotFound {});
+        let msg = ExecuteMsg::SetMinterCap {
+            minter: String::from("vault1"),
+            cap: Uint128::from(1000u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::MinterNotFound {});
+
+        let msg = ExecuteMsg::Mint {
+            recipient: String::from("addr0002"),
+            amount: Uint128::from(1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("vault1", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        assert_eq!(
+            query_minters(deps.as_ref(), None, None)
+                .unwrap()
+                .minters
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn balances_at_height() {
+        let mut deps = mock_dependencies(&[]);
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let minter = String::from("minter");
+        let mut env = mock_env();
+        let start = env.block.height;
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &addr1,
+            Uint128::from(1000u128),
+            &minter,
+            None,
+        );
+
+        env.block.height = start + 1;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::from(300u128),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+
+        env.block.height = start + 2;
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::from(500u128),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::from(200u128),
+        };
+        execute(deps.as_mut(), env, mock_info(&addr1, &[]), msg).unwrap();
+
+        // values are the ones at the beginning of the block
+        let balance_at = |address: &str, height: u64| {
+            query_balance_at_height(deps.as_ref(), address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        assert_eq!(balance_at(&addr1, start), Uint128::zero());
+        assert_eq!(balance_at(&addr1, start + 1), Uint128::from(1000u128));
+        assert_eq!(balance_at(&addr1, start + 2), Uint128::from(700u128));
+        assert_eq!(balance_at(&addr1, start + 3), Uint128::from(500u128));
+        assert_eq!(balance_at(&addr2, start + 1), Uint128::zero());
+        assert_eq!(balance_at(&addr2, start + 2), Uint128::from(300u128));
+        assert_eq!(balance_at(&addr2, start + 3), Uint128::from(800u128));
+
+        let supply_at = |height: u64| {
+            query_total_supply_at_height(deps.as_ref(), height)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(supply_at(start), Uint128::zero());
+        assert_eq!(supply_at(start + 1), Uint128::from(1000u128));
+        assert_eq!(supply_at(start + 2), Uint128::from(1000u128));
+        assert_eq!(supply_at(start + 3), Uint128::from(1300u128));
+        assert_eq!(
+            supply_at(start + 3),
+            query_token_info(deps.as_ref()).unwrap().total_supply
+        );
+
+        // nothing is known before the instantiation
+        query_balance_at_height(deps.as_ref(), addr1, start - 1).unwrap_err();
+        let err = query_total_supply_at_height(deps.as_ref(), start - 1).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(format!("No snapshots before height {}", start))
+        );
+    }
+
+    #[test]
+    fn several_changes_in_one_block() {
+        let mut deps = mock_dependencies(&[]);
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let minter = String::from("minter");
+        let mut env = mock_env();
+        let start = env.block.height;
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            &addr1,
+            Uint128::from(1000u128),
+            &minter,
+            None,
+        );
+
+        env.block.height = start + 1;
+        for _ in 0..2 {
+            let msg = ExecuteMsg::Transfer {
+                recipient: addr2.clone(),
+                amount: Uint128::from(300u128),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(&addr1, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr1.clone(),
+            amount: Uint128::from(100u128),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&addr2, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::from(50u128),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::from(20u128),
+        };
+        execute(deps.as_mut(), env, mock_info(&addr2, &[]), msg).unwrap();
+
+        // the block keeps the values from before its first change
+        let balance_at = |address: &str, height: u64| {
+            query_balance_at_height(deps.as_ref(), address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        let supply_at = |height: u64| {
+            query_total_supply_at_height(deps.as_ref(), height)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(balance_at(&addr1, start + 1), Uint128::from(1000u128));
+        assert_eq!(balance_at(&addr2, start + 1), Uint128::zero());
+        assert_eq!(supply_at(start + 1), Uint128::from(1000u128));
+        // and the next one starts with the values after its last change
+        assert_eq!(balance_at(&addr1, start + 2), Uint128::from(500u128));
+        assert_eq!(balance_at(&addr2, start + 2), Uint128::from(530u128));
+        assert_eq!(supply_at(start + 2), Uint128::from(1030u128));
+    }
+
+    #[test]
+    fn allowance_moves_are_snapshotted() {
+        let mut deps = mock_dependencies(&[]);
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let rcpt = String::from("addr0003");
+        let contract = String::from("cool-dex");
+        let mut env = mock_env();
+        let start = env.block.height;
+        do_instantiate(deps.as_mut(), &owner, Uint128::from(1000u128));
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::from(1000u128),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
+
+        env.block.height = start + 1;
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: rcpt.clone(),
+            amount: Uint128::from(100u128),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+
+        env.block.height = start + 2;
+        let msg = ExecuteMsg::SendFrom {
+            owner: owner.clone(),
+            contract: contract.clone(),
+            amount: Uint128::from(200u128),
+            msg: Binary::from(r#"{"some":123}"#.as_bytes()),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&spender, &[]), msg).unwrap();
+
+        env.block.height = start + 3;
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::from(300u128),
+        };
+        execute(deps.as_mut(), env, mock_info(&spender, &[]), msg).unwrap();
+
+        let balance_at = |address: &str, height: u64| {
+            query_balance_at_height(deps.as_ref(), address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        let supply_at = |height: u64| {
+            query_total_supply_at_height(deps.as_ref(), height)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(balance_at(&owner, start + 1), Uint128::from(1000u128));
+        assert_eq!(balance_at(&owner, start + 2), Uint128::from(900u128));
+        assert_eq!(balance_at(&owner, start + 3), Uint128::from(700u128));
+        assert_eq!(balance_at(&owner, start + 4), Uint128::from(400u128));
+        assert_eq!(balance_at(&rcpt, start + 1), Uint128::zero());
+        assert_eq!(balance_at(&rcpt, start + 2), Uint128::from(100u128));
+        assert_eq!(balance_at(&contract, start + 2), Uint128::zero());
+        assert_eq!(balance_at(&contract, start + 3), Uint128::from(200u128));
+        assert_eq!(supply_at(start + 3), Uint128::from(1000u128));
+        assert_eq!(supply_at(start + 4), Uint128::from(700u128));
+    }
+
+    #[test]
+    fn snapshots_start_after_migration() {
+        let mut deps = mock_dependencies(&[]);
+        let addr1 = Addr::unchecked("addr0001");
+        let addr2 = Addr::unchecked("addr0002");
+        let mut env = mock_env();
+        let start = env.block.height;
+
+        // a token stored before balances were checkpointed
+        let balances: cw_storage_plus::Map<&Addr, Uint128> = cw_storage_plus::Map::new("balance");
+        balances
+            .save(deps.as_mut().storage, &addr1, &Uint128::from(700u128))
+            .unwrap();
+        balances
+            .save(deps.as_mut().storage, &addr2, &Uint128::from(300u128))
+            .unwrap();
+        let info = TokenInfo {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            total_supply: Uint128::from(1000u128),
+            mint: None,
+        };
+        TOKEN_INFO.save(deps.as_mut().storage, &info).unwrap();
+        OWNER
+            .save(deps.as_mut().storage, &Addr::unchecked("creator"))
+            .unwrap();
+
+        migrate(deps.as_mut(), env.clone(), MigrateMsg { owner: None }).unwrap();
+
+        // the values up to the migration block are not known
+        let err = query_balance_at_height(deps.as_ref(), addr1.to_string(), start).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(format!("No snapshots before height {}", start + 1))
+        );
+        query_total_supply_at_height(deps.as_ref(), start).unwrap_err();
+
+        env.block.height = start + 5;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.to_string(),
+            amount: Uint128::from(200u128),
+        };
+        execute(deps.as_mut(), env, mock_info(addr1.as_str(), &[]), msg).unwrap();
+
+        let balance_at = |address: &Addr, height: u64| {
+            query_balance_at_height(deps.as_ref(), address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        let supply_at = |height: u64| {
+            query_total_supply_at_height(deps.as_ref(), height)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(balance_at(&addr1, start + 1), Uint128::from(700u128));
+        assert_eq!(balance_at(&addr1, start + 5), Uint128::from(700u128));
+        assert_eq!(balance_at(&addr1, start + 6), Uint128::from(500u128));
+        assert_eq!(balance_at(&addr2, start + 5), Uint128::from(300u128));
+        assert_eq!(balance_at(&addr2, start + 6), Uint128::from(500u128));
+        assert_eq!(supply_at(start + 1), Uint128::from(1000u128));
+        assert_eq!(supply_at(start + 6), Uint128::from(1000u128));
+    }
+
     mod marketing {
         use super::*;
 
@@ -1921,6 +2952,24 @@ mod tests {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    if let Some(owner) = msg.owner {
+        let owner = deps.api.addr_validate(&owner)?;
+        OWNER.save(deps.storage, &owner)?;
+    }
+
+    // holders were not counted before, recount the stored balances in pages,
+    // the first one here and the rest with CountHolders
+    if HOLDER_COUNT.may_load(deps.storage)?.is_none() {
+        start_holder_recount(deps.storage)?;
+        count_holders(deps.storage, MAX_COUNT_LIMIT as usize)?;
+    }
+
+    // the history starts after the migration block, earlier values are not known
+    if TOTAL_SUPPLY.may_load(deps.storage)?.is_none() {
+        let info = TOKEN_INFO.load(deps.storage)?;
+        TOTAL_SUPPLY.save(deps.storage, &info.total_supply, env.block.height)?;
+        SNAPSHOT_START.save(deps.storage, &(env.block.height + 1))?;
+    }
     Ok(Response::default())
 }
@@ -1,7 +1,8 @@
 use cosmwasm_std::{Deps, Order, StdResult};
 use cw20::{AllAccountsResponse, AllAllowancesResponse, AllowanceInfo};
 
-use crate::state::{ALLOWANCES, BALANCES};
+use crate::msg::{MinterInfo, MintersResponse};
+use crate::state::{ALLOWANCES, BALANCES, MINTERS};
 use cw_storage_plus::Bound;
 
 // settings for pagination
@@ -44,10 +45,13 @@ pub fn query_all_accounts(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
 
-    let accounts: Result<Vec<_>, _> = BALANCES
-        .keys(deps.storage, start, None, Order::Ascending)
-        .map(String::from_utf8)
+    let accounts: StdResult<Vec<String>> = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(String::from_utf8(k)?)
+        })
         .collect();
 
     Ok(AllAccountsResponse {
@@ -55,6 +59,28 @@ pub fn query_all_accounts(
     })
 }
 
+pub fn query_minters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MintersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let minters: StdResult<Vec<MinterInfo>> = MINTERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, cap) = item?;
+            Ok(MinterInfo {
+                minter: String::from_utf8(k)?,
+                cap,
+            })
+        })
+        .collect();
+    Ok(MintersResponse { minters: minters? })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,6 +24,12 @@ pub enum ContractError {
     #[error("Minting cannot exceed the cap")]
     CannotExceedCap {},
 
+    #[error("Minter is already registered")]
+    MinterAlreadyRegistered {},
+
+    #[error("Minter is not registered")]
+    MinterNotFound {},
+
     #[error("Logo binary data exceeds 5KB limit")]
     LogoTooBig {},
 
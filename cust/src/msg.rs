@@ -1,4 +1,4 @@
-use cosmwasm_std::{StdError, StdResult, Uint128};
+use cosmwasm_std::{to_binary, CosmosMsg, StdError, StdResult, Uint128, WasmMsg};
 use cw20::{Cw20Coin, Logo, MinterResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -131,6 +131,29 @@ pub enum ExecuteMsg {
     },
     /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
     UploadLogo(Logo),
+    /// If sent by the owner, sets the contract notified of every transfer between accounts.
+    /// None/null unregisters it.
+    UpdateAccountingHook { contract: Option<String> },
+    /// Only with the "mintable" extension. If sent by the owner, replaces the minter
+    /// and its cap.
+    UpdateMinter {
+        new_minter: String,
+        cap: Option<Uint128>,
+    },
+    /// If sent by the owner, transfers the ownership to new_owner.
+    UpdateOwner { new_owner: String },
+    /// Only with the "mintable" extension. If sent by the owner, allows minter to issue
+    /// up to cap tokens, next to the minter set at instantiation.
+    AddMinter { minter: String, cap: Uint128 },
+    /// Only with the "mintable" extension. If sent by the owner, revokes a minter added
+    /// with AddMinter.
+    RemoveMinter { minter: String },
+    /// Only with the "mintable" extension. If sent by the owner, sets how many more tokens
+    /// a minter added with AddMinter can issue.
+    SetMinterCap { minter: String, cap: Uint128 },
+    /// Continues the holder recount started by a migration over the next limit
+    /// accounts. Anyone can send it until the recount is done.
+    CountHolders { limit: Option<u32> },
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -138,6 +161,15 @@ pub enum QueryMsg {
     /// Returns the current balance of the given address, 0 if unset.
     /// Return type: BalanceResponse.
     Balance { address: String },
+    /// Returns the balance of the given address at the beginning of the block at height,
+    /// 0 if unset. Fails for heights before the instantiation, or up to the migration
+    /// block for contracts migrated from a version without snapshots.
+    /// Return type: BalanceResponse.
+    BalanceAtHeight { address: String, height: u64 },
+    /// Returns the total supply at the beginning of the block at height.
+    /// Fails for the same heights as BalanceAtHeight.
+    /// Return type: TotalSupplyResponse.
+    TotalSupplyAtHeight { height: u64 },
     /// Returns metadata on the contract - name, decimals, supply, etc.
     /// Return type: TokenInfoResponse.
     TokenInfo {},
@@ -174,8 +206,84 @@ pub enum QueryMsg {
     /// contract.
     /// Return type: DownloadLogoResponse.
     DownloadLogo {},
+    /// Returns the total supply and the number of addresses holding a non-zero balance.
+    /// After a migration the holder count only covers the accounts recounted so far,
+    /// until CountHolders reports done.
+    /// Return type: TokenStatsResponse.
+    TokenStats {},
+    /// Returns the contract notified of transfers, if any.
+    /// Return type: AccountingHookResponse.
+    AccountingHook {},
+    /// Returns who can replace the minter and the owner, if any.
+    /// Return type: OwnerResponse.
+    Owner {},
+    /// Only with "mintable" extension
+    /// Returns the minters added with AddMinter and their remaining cap. Supports pagination.
+    /// Return type: MintersResponse.
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalSupplyResponse {
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenStatsResponse {
+    pub total_supply: Uint128,
+    pub holder_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountingHookResponse {
+    pub contract: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerResponse {
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterInfo {
+    pub minter: String,
+    /// how many more tokens can be issued by the minter
+    pub cap: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfo>,
+}
+
+/// Sent to the accounting hook contract after amount moved from sender to recipient
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountingHookMsg {
+    BalanceMoved {
+        sender: String,
+        recipient: String,
+        amount: Uint128,
+    },
+}
+
+impl AccountingHookMsg {
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_binary(&self)?,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
 }
 
-/// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
\ No newline at end of file
+pub struct MigrateMsg {
+    /// Sets the owner, for contracts instantiated before ownership was stored
+    pub owner: Option<String>,
+}
\ No newline at end of file
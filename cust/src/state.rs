@@ -1,9 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
 use cw20::{AllowanceResponse, Logo, MarketingInfoResponse};
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Bound, Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -31,5 +31,118 @@ impl TokenInfo {
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
 pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
 pub const LOGO: Item<Logo> = Item::new("logo");
-pub const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+/// Balances are checkpointed on every change, so they can be read at any past height
+pub const BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance",
+    "balance__checkpoints",
+    "balance__changelog",
+    Strategy::EveryBlock,
+);
+/// First height the balance and supply checkpoints can answer for
+pub const SNAPSHOT_START: Item<u64> = Item::new("snapshot_start");
+/// Mirrors TokenInfo.total_supply, checkpointed on every change
+pub const TOTAL_SUPPLY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply",
+    "total_supply__checkpoints",
+    "total_supply__changelog",
+    Strategy::EveryBlock,
+);
 pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
+/// Address allowed to replace the minter and the owner
+pub const OWNER: Item<Addr> = Item::new("owner");
+/// Additional minters and how many more tokens each of them can issue
+pub const MINTERS: Map<&Addr, Uint128> = Map::new("minters");
+/// Contract notified of every transfer between accounts
+pub const ACCOUNTING_HOOK: Item<Addr> = Item::new("accounting_hook");
+/// Number of addresses holding a non-zero balance
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
+/// Set while HOLDER_COUNT is being recounted, to the last address counted so far
+pub const HOLDER_RECOUNT: Item<Option<Addr>> = Item::new("holder_recount");
+
+/// Sets the balance of address to the result of action on its current balance
+/// and keeps HOLDER_COUNT in sync when the balance goes from or to zero
+pub fn update_balance<A>(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    height: u64,
+    action: A,
+) -> StdResult<Uint128>
+where
+    A: FnOnce(Uint128) -> StdResult<Uint128>,
+{
+    let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+    let new_balance = action(balance)?;
+    BALANCES.save(storage, address, &new_balance, height)?;
+
+    // during a recount, addresses past the last counted one are counted when reached
+    let counted = match HOLDER_RECOUNT.may_load(storage)? {
+        Some(Some(last)) => address.as_str() <= last.as_str(),
+        Some(None) => false,
+        None => true,
+    };
+    if counted {
+        if balance.is_zero() && !new_balance.is_zero() {
+            HOLDER_COUNT.update(storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+        } else if !balance.is_zero() && new_balance.is_zero() {
+            HOLDER_COUNT.update(storage, |count| -> StdResult<_> { Ok(count - 1) })?;
+        }
+    }
+    Ok(new_balance)
+}
+
+pub fn increase_balance(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    height: u64,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    update_balance(storage, address, height, |balance| Ok(balance + amount))
+}
+
+pub fn decrease_balance(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    height: u64,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    update_balance(storage, address, height, |balance| {
+        Ok(balance.checked_sub(amount)?)
+    })
+}
+
+/// Recounts the holders from BALANCES, with HOLDER_COUNT starting at zero
+pub fn start_holder_recount(storage: &mut dyn Storage) -> StdResult<()> {
+    HOLDER_COUNT.save(storage, &0)?;
+    HOLDER_RECOUNT.save(storage, &None)
+}
+
+/// Adds the holders among the next limit addresses of a recount to HOLDER_COUNT,
+/// returns true once every address has been counted
+pub fn count_holders(storage: &mut dyn Storage, limit: usize) -> StdResult<bool> {
+    let last = match HOLDER_RECOUNT.may_load(storage)? {
+        Some(last) => last,
+        None => return Ok(true),
+    };
+    let start = last.map(|last| Bound::exclusive(last.as_str()));
+    let balances: Vec<(Vec<u8>, Uint128)> = BALANCES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let holders = balances
+        .iter()
+        .filter(|(_, balance)| !balance.is_zero())
+        .count() as u64;
+    HOLDER_COUNT.update(storage, |count| -> StdResult<_> { Ok(count + holders) })?;
+    match balances.last() {
+        Some((address, _)) if balances.len() == limit => {
+            let address = Addr::unchecked(String::from_utf8(address.clone())?);
+            HOLDER_RECOUNT.save(storage, &Some(address))?;
+            Ok(false)
+        }
+        _ => {
+            HOLDER_RECOUNT.remove(storage);
+            Ok(true)
+        }
+    }
+}
@@ -1,26 +1,55 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use crate::deposit::{deposit, redeem_stable};
+use crate::deposit::{balance_moved, deposit, harvest, record_rate_snapshot, redeem_stable};
+use crate::error::ContractError;
+use crate::migrations::migrate_v04_config;
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RedeemStableHookMsg};
+use crate::orders::{
+    cancel_order, create_order, execute_orders, fund_escrow, update_order, withdraw_escrow,
+};
 use crate::querier::{
-    calculate_aterra_profit, query_capacorp_all_accounts, query_capapult_exchange_rate,
-    query_capapult_rate, query_config, query_dashboard,
-    query_harvest_value, query_harvested_sum, query_token_balance, query_token_supply,
+    calculate_aterra_profit, compute_holder_profit, query_account_history, query_account_summary,
+    query_accounting_hook, query_capa_yield, query_capacorp_all_accounts,
+    query_capapult_exchange_rate, query_capapult_rate, query_claimed_profit, query_config,
+    query_dashboard, query_distribution_schedule, query_harvest_value, query_harvested_sum,
+    query_implied_apy, query_market_state, query_pending_owner, query_pending_profit,
+    query_project_balance, query_project_deposit, query_rate_history, query_realized_apy,
+    query_token_balance, query_token_supply, query_user_orders, query_yield_schedule,
+    settle_holder_profit,
 };
 
 use crate::state::{
-    read_config, read_profit, remove_account, store_config, store_profit, Config,
+    read_config, read_insurance, read_last_distribution, read_pending_owner, read_profit,
+    read_profit_dust, read_profit_index, read_reserved_profit, read_unclaimed_profit,
+    remove_account, remove_history, remove_pending_owner, store_config, store_holder_info,
+    store_insurance, store_last_distribution, store_pending_owner, store_profit, store_profit_dust,
+    store_profit_index, store_unclaimed_profit, store_yield_schedule, Config, PendingOwner,
+    YieldSchedule,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, Attribute, Binary, CanonicalAddr, CosmosMsg, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    Env, MessageInfo, Response, StdResult, Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::{set_contract_version, CONTRACT};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:earn-contract";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const _1M_: u128 = 1000000;
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 100 * _1M_;
+/// Upper bound of the profit share sent to the insurance contract, in percent
+pub const MAX_INSURANCE_SHARE: u64 = 50;
+/// Upper bound of the profit share paid to a keeper calling Distribute, in percent
+pub const MAX_KEEPER_BOUNTY: u64 = 10;
+pub const DEFAULT_KEEPER_INTERVAL: u64 = 86400;
+/// Longest keeper interval accepted by UpdateConfig, in seconds (one year)
+pub const MAX_KEEPER_INTERVAL: u64 = 365 * 86400;
+/// Longest capa_yield ramp accepted by UpdateYield, in seconds (one year)
+pub const MAX_YIELD_RAMP_DURATION: u64 = 365 * 86400;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,7 +57,7 @@ pub fn instantiate(
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let initial_deposit = info
         .funds
         .iter()
@@ -37,21 +66,22 @@ pub fn instantiate(
         .unwrap_or_else(Uint128::zero);
 
     if initial_deposit != INITIAL_DEPOSIT_AMOUNT.into() {
-        return Err(StdError::generic_err(format!(
-            "Must deposit initial funds {:?}{:?}",
-            INITIAL_DEPOSIT_AMOUNT, msg.stable_denom
-        )));
+        return Err(ContractError::InvalidInitialDeposit {
+            amount: INITIAL_DEPOSIT_AMOUNT.into(),
+            denom: msg.stable_denom,
+        });
     }
+    validate_capa_yield(msg.capa_yield)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     store_profit(deps.storage, &Uint256::zero())?;
+    store_insurance(deps.storage, &Uint256::zero())?;
 
     let result = deps.api.addr_validate(&msg.owner_addr);
     match result {
         Ok(_x) => {}
-        Err(_x) => {
-            return Err(StdError::generic_err(
-                "Owner address does not pass validation",
-            ))
-        }
+        Err(_x) => return Err(ContractError::InvalidOwnerAddress {}),
     }
 
     store_config(
@@ -59,6 +89,7 @@ pub fn instantiate(
         &Config {
             contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
             owner_addr: deps.api.addr_canonicalize(&msg.owner_addr)?,
+            guardian_addr: deps.api.addr_canonicalize(&msg.owner_addr)?,
             stable_denom: msg.stable_denom,
             market_contract: CanonicalAddr::from(vec![]),
             aterra_contract: CanonicalAddr::from(vec![]),
@@ -66,7 +97,17 @@ pub fn instantiate(
             capacorp_contract: CanonicalAddr::from(vec![]),
             capa_contract: CanonicalAddr::from(vec![]),
             insurance_contract: CanonicalAddr::from(vec![]),
-            capa_yield: msg.capa_yield
+            capa_yield: msg.capa_yield,
+            insurance_share: Decimal256::zero(),
+            min_deposit: Uint256::from(_1M_),
+            min_withdraw: Uint256::from(_1M_),
+            min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+            deposit_paused: false,
+            redeem_paused: false,
+            distribute_paused: false,
+            keeper_enabled: false,
+            keeper_interval: DEFAULT_KEEPER_INTERVAL,
+            keeper_bounty: Decimal256::zero(),
         },
     )?;
 
@@ -74,7 +115,12 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::RegisterContracts {
             market_contract,
@@ -93,21 +139,133 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             &capa_contract,
             &insurance_contract,
         ),
-        ExecuteMsg::UpdateConfig { owner_addr } => update_config(deps, info, owner_addr),
-        ExecuteMsg::Distribute {} => distribute(deps, env, info),
-        ExecuteMsg::Deposit {} => deposit(deps, info),
+        ExecuteMsg::UpdateContracts {
+            market_contract,
+            aterra_contract,
+            cterra_contract,
+            capacorp_contract,
+            capa_contract,
+            insurance_contract,
+        } => update_contracts(
+            deps,
+            info,
+            market_contract,
+            aterra_contract,
+            cterra_contract,
+            capacorp_contract,
+            capa_contract,
+            insurance_contract,
+        ),
+        ExecuteMsg::UpdateYield { target, duration } => {
+            update_yield(deps, env, info, target, duration)
+        }
+        ExecuteMsg::UpdateConfig {
+            guardian_addr,
+            insurance_share,
+            min_deposit,
+            min_withdraw,
+            min_distribute_profit,
+            keeper_enabled,
+            keeper_interval,
+            keeper_bounty,
+        } => update_config(
+            deps,
+            info,
+            guardian_addr,
+            insurance_share,
+            min_deposit,
+            min_withdraw,
+            min_distribute_profit,
+            keeper_enabled,
+            keeper_interval,
+            keeper_bounty,
+        ),
+        ExecuteMsg::ProposeNewOwner {
+            owner_addr,
+            expires,
+        } => propose_new_owner(deps, env, info, owner_addr, expires),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::Pause {
+            deposit,
+            redeem,
+            distribute,
+        } => pause(deps, info, deposit, redeem, distribute),
+        ExecuteMsg::Unpause {
+            deposit,
+            redeem,
+            distribute,
+        } => unpause(deps, info, deposit, redeem, distribute),
+        ExecuteMsg::Distribute {} => {
+            if read_config(deps.storage)?.distribute_paused {
+                return Err(ContractError::DistributePaused {});
+            }
+            distribute(deps, env, info)
+        }
+        ExecuteMsg::ClaimProfit {} => {
+            if read_config(deps.storage)?.distribute_paused {
+                return Err(ContractError::DistributePaused {});
+            }
+            claim_profit(deps, info)
+        }
+        ExecuteMsg::Deposit { recipient } => {
+            if read_config(deps.storage)?.deposit_paused {
+                return Err(ContractError::DepositPaused {});
+            }
+            deposit(deps, env, info, recipient)
+        }
+        ExecuteMsg::Harvest { amount } => {
+            if read_config(deps.storage)?.redeem_paused {
+                return Err(ContractError::RedeemPaused {});
+            }
+            harvest(deps, env, info, amount)
+        }
+        ExecuteMsg::FundEscrow {} => {
+            if read_config(deps.storage)?.deposit_paused {
+                return Err(ContractError::DepositPaused {});
+            }
+            fund_escrow(deps, info)
+        }
+        ExecuteMsg::WithdrawEscrow { amount } => withdraw_escrow(deps, info, amount),
+        ExecuteMsg::CreateOrder { kind, interval } => create_order(deps, env, info, kind, interval),
+        ExecuteMsg::UpdateOrder { id, kind, interval } => {
+            update_order(deps, info, id, kind, interval)
+        }
+        ExecuteMsg::CancelOrder { id } => cancel_order(deps, info, id),
+        ExecuteMsg::ExecuteOrders { limit } => {
+            if read_config(deps.storage)?.deposit_paused {
+                return Err(ContractError::DepositPaused {});
+            }
+            execute_orders(deps, env, limit)
+        }
         ExecuteMsg::RemoveAccount { addr } => remove_info_account(deps, info, addr),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::BalanceMoved {
+            sender,
+            recipient,
+            amount,
+        } => {
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(info.sender.as_str())? == config.capacorp_contract {
+                capacorp_moved(deps, info, sender, recipient, amount)
+            } else {
+                balance_moved(deps, env, info, sender, recipient, amount)
+            }
+        }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::ExchangeRate {} => to_binary(&query_capapult_exchange_rate(deps)?),
-        QueryMsg::Dashboard {} => to_binary(&query_dashboard(deps)?),
-        QueryMsg::CorpAccounts {} => to_binary(&query_capacorp_all_accounts(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::ExchangeRate {} => to_binary(&query_capapult_exchange_rate(
+            deps,
+            env.block.time.seconds(),
+        )?),
+        QueryMsg::Dashboard {} => to_binary(&query_dashboard(deps, env.block.time.seconds())?),
+        QueryMsg::CorpAccounts {} => to_binary(&query_capacorp_all_accounts(deps, None, None)?),
         QueryMsg::AvailableHarvest { account_addr } => {
             let config: Config = read_config(deps.storage)?;
             let cust_balance = query_token_balance(
@@ -115,7 +273,12 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 &deps.api.addr_humanize(&config.cterra_contract)?,
                 &deps.api.addr_validate(account_addr.as_str())?,
             )?;
-            to_binary(&query_harvest_value(deps, cust_balance, account_addr)?)
+            to_binary(&query_harvest_value(
+                deps,
+                env.block.time.seconds(),
+                cust_balance,
+                account_addr,
+            )?)
         }
         QueryMsg::HarvestedSum { account_addr } => {
             to_binary(&query_harvested_sum(deps, account_addr)?)
@@ -134,33 +297,100 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         )?),
         QueryMsg::QueryCapapultRate {} => to_binary(&query_capapult_rate(
             deps,
-        )?)
+            env.block.time.seconds(),
+        )?),
+        QueryMsg::YieldSchedule {} => {
+            to_binary(&query_yield_schedule(deps, env.block.time.seconds())?)
+        }
+        QueryMsg::PendingProfit { address } => to_binary(&query_pending_profit(deps, address)?),
+        QueryMsg::ClaimedProfit { address } => to_binary(&query_claimed_profit(deps, address)?),
+        QueryMsg::DistributionSchedule {} => to_binary(&query_distribution_schedule(deps)?),
+        QueryMsg::UserOrders { address } => to_binary(&query_user_orders(deps, address)?),
+        QueryMsg::AccountHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_account_history(deps, address, start_after, limit)?),
+        QueryMsg::RateHistory { start_after, limit } => {
+            to_binary(&query_rate_history(deps, start_after, limit)?)
+        }
+        QueryMsg::RealizedApy { window_seconds } => to_binary(&query_realized_apy(
+            deps,
+            env.block.time.seconds(),
+            window_seconds,
+        )?),
+        QueryMsg::ProjectBalance { address, days } => to_binary(&query_project_balance(
+            deps,
+            env.block.time.seconds(),
+            address,
+            days,
+        )?),
+        QueryMsg::ProjectDeposit { amount, days } => to_binary(&query_project_deposit(
+            deps,
+            env.block.time.seconds(),
+            amount,
+            days,
+        )?),
+        QueryMsg::ImpliedApy {} => to_binary(&query_implied_apy(deps, env.block.time.seconds())?),
+        QueryMsg::AccountSummary { address } => to_binary(&query_account_summary(
+            deps,
+            env.block.time.seconds(),
+            address,
+        )?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    match CONTRACT.may_load(deps.storage)? {
+        // v0.4.x did not store a contract version
+        None => migrate_v04_config(deps.storage)?,
+        // nothing else to migrate from yet
+        Some(version) => {
+            if version.contract != CONTRACT_NAME || version.version != CONTRACT_VERSION {
+                return Err(ContractError::UnsupportedMigration {
+                    contract: version.contract,
+                    version: version.version,
+                });
+            }
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::default())
 }
 
+pub fn validate_capa_yield(capa_yield: Decimal256) -> Result<(), ContractError> {
+    if capa_yield.is_zero() || capa_yield > Decimal256::one() {
+        return Err(ContractError::InvalidCapaYield { capa_yield });
+    }
+    Ok(())
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let contract_addr = info.sender;
     let msg = cw20_msg.msg;
 
     match from_binary(&msg)? {
-        RedeemStableHookMsg::RedeemStable {} => {
+        RedeemStableHookMsg::RedeemStable {
+            recipient,
+            min_receive,
+        } => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.cterra_contract {
-                return Err(StdError::generic_err("Unauthorized"));
+                return Err(ContractError::Unauthorized {});
+            }
+            if config.redeem_paused {
+                return Err(ContractError::RedeemPaused {});
             }
             let sender = deps.api.addr_validate(&cw20_msg.sender)?;
-            redeem_stable(deps, env, sender, cw20_msg.amount)
+            redeem_stable(deps, env, sender, cw20_msg.amount, recipient, min_receive)
         }
     }
 }
@@ -174,7 +404,7 @@ pub fn register_contracts(
     capacorp_contract: &str,
     capa_contract: &str,
     insurance_contract: &str,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if config.aterra_contract != CanonicalAddr::from(vec![])
         || config.market_contract != CanonicalAddr::from(vec![])
@@ -183,12 +413,12 @@ pub fn register_contracts(
         || config.capa_contract != CanonicalAddr::from(vec![])
         || config.insurance_contract != CanonicalAddr::from(vec![])
     {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::ContractsAlreadyRegistered {});
     }
 
     // permission check
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     deps.api.addr_validate(&market_contract)?;
@@ -210,102 +440,490 @@ pub fn register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn update_contracts(
+    deps: DepsMut,
+    info: MessageInfo,
+    market_contract: Option<String>,
+    aterra_contract: Option<String>,
+    cterra_contract: Option<String>,
+    capacorp_contract: Option<String>,
+    capa_contract: Option<String>,
+    insurance_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.market_contract == CanonicalAddr::from(vec![]) {
+        return Err(ContractError::ContractsNotRegistered {});
+    }
+
+    let mut attributes: Vec<Attribute> = vec![attr("action", "update_contracts")];
+
+    if let Some(market_contract) = market_contract {
+        let addr = deps.api.addr_validate(&market_contract)?;
+        query_market_state(deps.as_ref(), addr.clone()).map_err(|_| {
+            ContractError::InvalidContract {
+                kind: "market".to_string(),
+                addr: addr.to_string(),
+            }
+        })?;
+        config.market_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("market_contract", addr));
+    }
+
+    if let Some(aterra_contract) = aterra_contract {
+        let addr = validate_token_contract(deps.as_ref(), "aterra", &aterra_contract)?;
+        config.aterra_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("aterra_contract", addr));
+    }
+
+    if let Some(cterra_contract) = cterra_contract {
+        let addr = validate_token_contract(deps.as_ref(), "cterra", &cterra_contract)?;
+        config.cterra_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("cterra_contract", addr));
+    }
+
+    if let Some(capacorp_contract) = capacorp_contract {
+        let addr = validate_token_contract(deps.as_ref(), "capacorp", &capacorp_contract)?;
+        config.capacorp_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("capacorp_contract", addr));
+    }
+
+    if let Some(capa_contract) = capa_contract {
+        let addr = validate_token_contract(deps.as_ref(), "capa", &capa_contract)?;
+        config.capa_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("capa_contract", addr));
+    }
+
+    if let Some(insurance_contract) = insurance_contract {
+        let addr = deps.api.addr_validate(&insurance_contract)?;
+        config.insurance_contract = deps.api.addr_canonicalize(addr.as_str())?;
+        attributes.push(attr("insurance_contract", addr));
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Checks that the address answers a cw20 TokenInfo query
+fn validate_token_contract(deps: Deps, kind: &str, contract: &str) -> Result<Addr, ContractError> {
+    let addr = deps.api.addr_validate(contract)?;
+    query_token_supply(deps, addr.clone()).map_err(|_| ContractError::InvalidContract {
+        kind: kind.to_string(),
+        addr: addr.to_string(),
+    })?;
+    Ok(addr)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
-) -> StdResult<Response> {
+    guardian_addr: Option<Addr>,
+    insurance_share: Option<Decimal256>,
+    min_deposit: Option<Uint256>,
+    min_withdraw: Option<Uint256>,
+    min_distribute_profit: Option<Uint256>,
+    keeper_enabled: Option<bool>,
+    keeper_interval: Option<u64>,
+    keeper_bounty: Option<Decimal256>,
+) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
     // permission check
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(guardian_addr) = guardian_addr {
+        config.guardian_addr = deps.api.addr_canonicalize(guardian_addr.as_str())?;
+    }
+
+    if let Some(insurance_share) = insurance_share {
+        let max = Decimal256::percent(MAX_INSURANCE_SHARE);
+        if insurance_share > max {
+            return Err(ContractError::InsuranceShareTooHigh { max });
+        }
+        config.insurance_share = insurance_share;
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
+    if let Some(min_deposit) = min_deposit {
+        config.min_deposit = min_deposit;
+    }
+
+    if let Some(min_withdraw) = min_withdraw {
+        config.min_withdraw = min_withdraw;
+    }
+
+    if let Some(min_distribute_profit) = min_distribute_profit {
+        config.min_distribute_profit = min_distribute_profit;
+    }
+
+    if let Some(keeper_enabled) = keeper_enabled {
+        config.keeper_enabled = keeper_enabled;
+    }
+
+    if let Some(keeper_interval) = keeper_interval {
+        if keeper_interval > MAX_KEEPER_INTERVAL {
+            return Err(ContractError::KeeperIntervalTooLong {
+                max: MAX_KEEPER_INTERVAL,
+            });
+        }
+        config.keeper_interval = keeper_interval;
+    }
+
+    if let Some(keeper_bounty) = keeper_bounty {
+        let max = Decimal256::percent(MAX_KEEPER_BOUNTY);
+        if keeper_bounty > max {
+            return Err(ContractError::KeeperBountyTooHigh { max });
+        }
+        config.keeper_bounty = keeper_bounty;
+    }
+
+    if config.min_deposit.is_zero()
+        || config.min_withdraw.is_zero()
+        || config.min_distribute_profit.is_zero()
+    {
+        return Err(ContractError::InvalidMinimum {});
+    }
+
+    // a zero interval would let anyone take the bounty every block
+    if config.keeper_enabled && config.keeper_interval == 0 {
+        return Err(ContractError::InvalidKeeperInterval {});
     }
 
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+pub fn update_yield(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    target: Decimal256,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_capa_yield(target)?;
+    if duration > MAX_YIELD_RAMP_DURATION {
+        return Err(ContractError::YieldRampTooLong {
+            max: MAX_YIELD_RAMP_DURATION,
+        });
+    }
+
+    // start from the effective yield so an update during a ramp does not jump
+    let now = env.block.time.seconds();
+    let end_time = now
+        .checked_add(duration)
+        .ok_or(ContractError::YieldRampTooLong {
+            max: MAX_YIELD_RAMP_DURATION,
+        })?;
+    let schedule = YieldSchedule {
+        start_yield: query_capa_yield(deps.as_ref(), now)?,
+        target_yield: target,
+        start_time: now,
+        end_time,
+    };
+    store_yield_schedule(deps.storage, &schedule)?;
+
+    config.capa_yield = target;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_yield"),
+        attr("start_yield", schedule.start_yield.to_string()),
+        attr("target_yield", schedule.target_yield.to_string()),
+        attr("end_time", schedule.end_time.to_string()),
+    ]))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner_addr: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+
+    let new_owner = deps.api.addr_validate(&owner_addr)?;
+    store_pending_owner(
+        deps.storage,
+        &PendingOwner {
+            owner_addr: deps.api.addr_canonicalize(new_owner.as_str())?,
+            expires,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner_addr", new_owner),
+        attr("expires", expires.to_string()),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if read_pending_owner(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_proposal"))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_owner = match read_pending_owner(deps.storage)? {
+        Some(p) => p,
+        None => return Err(ContractError::NoPendingOwner {}),
+    };
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != pending_owner.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if pending_owner.expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner_addr = pending_owner.owner_addr;
+    store_config(deps.storage, &config)?;
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner_addr", info.sender),
+    ]))
+}
+
+pub fn pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    deposit: bool,
+    redeem: bool,
+    distribute: bool,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender != config.guardian_addr && sender != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.deposit_paused |= deposit;
+    config.redeem_paused |= redeem;
+    config.distribute_paused |= distribute;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "pause"),
+        attr("deposit_paused", config.deposit_paused.to_string()),
+        attr("redeem_paused", config.redeem_paused.to_string()),
+        attr("distribute_paused", config.distribute_paused.to_string()),
+    ]))
+}
+
+pub fn unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    deposit: bool,
+    redeem: bool,
+    distribute: bool,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.deposit_paused &= !deposit;
+    config.redeem_paused &= !redeem;
+    config.distribute_paused &= !distribute;
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unpause"),
+        attr("deposit_paused", config.deposit_paused.to_string()),
+        attr("redeem_paused", config.redeem_paused.to_string()),
+        attr("distribute_paused", config.distribute_paused.to_string()),
+    ]))
+}
+
 pub fn remove_info_account(
     deps: DepsMut,
     info: MessageInfo,
     account_addr: Option<Addr>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     // permission check
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(StdError::generic_err("Unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     if let Some(account_addr) = account_addr {
         let canonical_addr = deps.api.addr_canonicalize(account_addr.as_str())?;
         remove_account(deps.storage, &canonical_addr);
+        remove_history(deps.storage, &canonical_addr)?;
     }
     Ok(Response::new())
 }
 
-fn transfer_capacorp(
-    deps: DepsMut,
-    config: Config,
-    insurance_amount: Uint256,
-    profit_amount: Uint256,
-) -> StdResult<Response> {
-    let mut messages: Vec<CosmosMsg> = Vec::new();
-    let mut logs: Vec<Attribute> = vec![attr("action", "distribute")];
-
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-        funds: vec![],
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: deps
-                .api
-                .addr_humanize(&config.insurance_contract)?
-                .to_string(),
-            amount: insurance_amount.into(),
-        })?,
-    }));
+pub fn claim_profit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut holder = compute_holder_profit(deps.as_ref(), &info.sender)?;
 
-    let insurance_str: String = insurance_amount.into();
-    logs.push(attr("insurance", insurance_str));
+    // never pay out more than what was distributed and is still unclaimed
+    let mut unclaimed_profit = read_unclaimed_profit(deps.storage)?;
+    let mut amount = holder.pending_profit;
+    if amount > unclaimed_profit {
+        amount = unclaimed_profit;
+    }
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
 
-    let stake_holders = query_capacorp_all_accounts(deps.as_ref())?;
+    holder.pending_profit = holder.pending_profit - amount;
+    holder.claimed_profit += amount;
+    store_holder_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+        &holder,
+    )?;
+    unclaimed_profit = unclaimed_profit - amount;
+    store_unclaimed_profit(deps.storage, &unclaimed_profit)?;
 
-    for stake_holder in stake_holders {
-        let percent = query_token_balance(
-            deps.as_ref(),
-            &deps.api.addr_humanize(&config.capacorp_contract)?,
-            &deps.api.addr_validate(&stake_holder)?,
-        )?;
-        let share = profit_amount * percent * Decimal256::from_ratio(1, 100000);
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
             funds: vec![],
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: stake_holder.clone(),
-                amount: share.into(),
+                recipient: info.sender.to_string(),
+                amount: amount.into(),
             })?,
-        }));
+        }))
+        .add_attributes(vec![
+            attr("action", "claim_profit"),
+            attr("holder", info.sender),
+            attr("amount", amount),
+        ]))
+}
 
-        let share_str: String = share.into();
-        logs.push(attr(stake_holder.clone().as_str(), share_str));
+/// Settles the profit of both sides of a capacorp transfer at their balances before the
+/// move, so profit distributed before the transfer stays with the sender.
+/// Capacorp has a fixed supply, minted before any distribution, so only transfers move it.
+/// Capacorp runs the cUST token code with this contract as its accounting hook, which
+/// reports every transfer, including those to this contract.
+pub fn capacorp_moved(
+    deps: DepsMut,
+    info: MessageInfo,
+    sender: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.capacorp_contract {
+        return Err(ContractError::Unauthorized {});
     }
-    let total_profit = read_profit(deps.storage)?;
-    let total_profit = total_profit + profit_amount;
-    store_profit(deps.storage, &total_profit)?;
-    Ok(Response::new().add_messages(messages).add_attributes(logs))
+
+    let sender_addr = deps.api.addr_validate(&sender)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let amount = Uint256::from(amount);
+
+    // a transfer to oneself leaves the balance as it was, there is nothing to settle
+    if sender_addr != recipient_addr {
+        // capacorp balances are already updated
+        let balance = query_token_balance(deps.as_ref(), &info.sender, &sender_addr)?;
+        let holder = settle_holder_profit(deps.as_ref(), &sender_addr, balance + amount)?;
+        store_holder_info(
+            deps.storage,
+            &deps.api.addr_canonicalize(sender_addr.as_str())?,
+            &holder,
+        )?;
+
+        let balance = query_token_balance(deps.as_ref(), &info.sender, &recipient_addr)?;
+        let holder = settle_holder_profit(deps.as_ref(), &recipient_addr, balance - amount)?;
+        store_holder_info(
+            deps.storage,
+            &deps.api.addr_canonicalize(recipient_addr.as_str())?,
+            &holder,
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "capacorp_moved"),
+        attr("sender", sender),
+        attr("recipient", recipient),
+        attr("amount", amount),
+    ]))
 }
 
-pub fn distribute(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+pub fn distribute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(StdError::generic_err("Unauthorized"));
+    // anyone may distribute in keeper mode once the interval has passed
+    let is_keeper = deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr;
+    if is_keeper {
+        if !config.keeper_enabled {
+            return Err(ContractError::Unauthorized {});
+        }
+        let next_time = read_last_distribution(deps.storage)? + config.keeper_interval;
+        if env.block.time.seconds() < next_time {
+            return Err(ContractError::DistributeTooEarly { next_time });
+        }
+    }
+
+    // holders are settled on capacorp transfers, a plain cw20 capacorp cannot report them
+    let capacorp_hook = query_accounting_hook(
+        deps.as_ref(),
+        &deps.api.addr_humanize(&config.capacorp_contract)?,
+    )
+    .unwrap_or(None);
+    if capacorp_hook != Some(env.contract.address.to_string()) {
+        return Err(ContractError::CapacorpHookNotRegistered {});
     }
+    record_rate_snapshot(deps.branch(), env.block.time.seconds())?;
 
     let cust_total_supply = query_token_supply(
         deps.as_ref(),
@@ -314,24 +932,103 @@ pub fn distribute(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Respo
 
     let mut profit = calculate_aterra_profit(
         deps.as_ref(),
+        env.block.time.seconds(),
         &env.contract.address,
         &deps.api.addr_humanize(&config.aterra_contract)?,
         cust_total_supply,
     )?;
 
-    // add insurance share here - zero for now
-    let insurance_share = Decimal256::from_ratio(0, 100);
-    let insurance_amount = profit * insurance_share;
+    let insurance_amount = profit * config.insurance_share;
 
     profit = profit - insurance_amount;
 
-    // take profit when at least there is at least 100 USD of profit
-    if profit < Uint256::from(INITIAL_DEPOSIT_AMOUNT) {
-        return Err(StdError::GenericErr {
-            msg: format!("Too little profit to distribute: {}", profit),
+    // rounding dust left over by the previous distribution
+    let profit_dust = read_profit_dust(deps.storage)?;
+    profit += profit_dust;
+
+    // take profit only above the configured threshold
+    if profit < config.min_distribute_profit {
+        return Err(ContractError::ProfitBelowThreshold {
+            profit,
+            min: config.min_distribute_profit,
         });
     }
 
-    let response = transfer_capacorp(deps, config, insurance_amount, profit)?;
-    Ok(response)
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if insurance_amount > Uint256::zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps
+                    .api
+                    .addr_humanize(&config.insurance_contract)?
+                    .to_string(),
+                amount: insurance_amount.into(),
+            })?,
+        }));
+    }
+
+    let mut bounty_amount = Uint256::zero();
+    if is_keeper {
+        bounty_amount = profit * config.keeper_bounty;
+        profit = profit - bounty_amount;
+    }
+    if bounty_amount > Uint256::zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: bounty_amount.into(),
+            })?,
+        }));
+    }
+
+    // holders claim their share of the profit through ClaimProfit
+    let capacorp_supply = query_token_supply(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.capacorp_contract)?,
+    )?;
+    if capacorp_supply.is_zero() {
+        return Err(ContractError::NoCapacorpSupply {});
+    }
+    let index_delta = Decimal256::from_ratio(profit, capacorp_supply);
+    let distributed_amount = capacorp_supply * index_delta;
+    let profit_dust = profit - distributed_amount;
+    store_profit_dust(deps.storage, &profit_dust)?;
+
+    let profit_index = read_profit_index(deps.storage)? + index_delta;
+    store_profit_index(deps.storage, &profit_index)?;
+    let unclaimed_profit = read_unclaimed_profit(deps.storage)? + distributed_amount;
+    store_unclaimed_profit(deps.storage, &unclaimed_profit)?;
+
+    // what is owed to capacorp holders must stay backed by the aUST left after this call
+    let reserved = read_reserved_profit(deps.storage)?;
+    let balance = query_token_balance(
+        deps.as_ref(),
+        &deps.api.addr_humanize(&config.aterra_contract)?,
+        &env.contract.address,
+    )?;
+    if reserved + insurance_amount + bounty_amount > balance {
+        return Err(ContractError::ReservedProfitExceedsBalance { reserved, balance });
+    }
+
+    let total_profit = read_profit(deps.storage)? + distributed_amount;
+    store_profit(deps.storage, &total_profit)?;
+    let total_insurance = read_insurance(deps.storage)? + insurance_amount;
+    store_insurance(deps.storage, &total_insurance)?;
+    store_last_distribution(deps.storage, env.block.time.seconds())?;
+
+    let insurance_str: String = insurance_amount.into();
+    let profit_str: String = distributed_amount.into();
+    let dust_str: String = profit_dust.into();
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "distribute"),
+        attr("insurance", insurance_str),
+        attr("profit", profit_str),
+        attr("dust", dust_str),
+        attr("bounty", bounty_amount),
+        attr("profit_index", profit_index.to_string()),
+    ]))
 }
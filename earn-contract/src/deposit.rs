@@ -1,96 +1,153 @@
-use crate::msg::{DepositStableHandleMsg, RedeemStableHookMsg};
+use crate::error::ContractError;
+use crate::math::{Calculate, ExchangeRate};
+use crate::msg::{AccountOp, DepositStableHandleMsg, RedeemStableHandleMsg};
 use crate::querier::{
-    compute_tax, deduct_tax, query_capapult_exchange_rate, query_exchange_rate, query_token_balance,
+    compute_tax, deduct_tax, query_capa_yield, query_capapult_exchange_rate, query_exchange_rate,
+    query_harvest_value, query_token_allowance, query_token_balance,
 };
 use crate::state::{
-    read_config, read_last_ops_ust, read_total_claim, read_total_deposit, store_last_ops_ust,
-    store_total_claim, store_total_deposit, Config,
+    append_history, read_config, read_last_ops_ust, read_rate_snapshot, read_reserved_profit,
+    read_total_claim, read_total_deposit, store_last_ops_ust, store_rate_snapshot,
+    store_total_claim, store_total_deposit, Config, HistoryEntry, RateSnapshot,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, Uint128, WasmMsg,
+    Response, StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
 extern crate base64;
 
-pub fn deposit(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+/// Number of operations kept in the history of an account
+pub const MAX_HISTORY_ENTRIES: u64 = 100;
+/// Length of the periods keeping one rate snapshot each, in seconds
+pub const RATE_SNAPSHOT_PERIOD: u64 = 3600;
+
+pub fn deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
 
     // Check base denom deposit
-    let mut deposit_amount: Uint256 = info
+    let deposit_amount: Uint256 = info
         .funds
         .iter()
         .find(|c| c.denom == config.stable_denom)
         .map(|c| Uint256::from(c.amount))
         .unwrap_or_else(Uint256::zero);
 
+    let (messages, deposit_amount, mint_amount) =
+        deposit_stable(deps, &env, &recipient, deposit_amount)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "deposit_stable"),
+        attr("depositor", info.sender),
+        attr("recipient", recipient),
+        attr("mint_amount", mint_amount),
+        attr("deposit_amount", deposit_amount),
+    ]))
+}
+
+/// Deposits amount UST held by this contract into the market and mints cUST to recipient.
+/// Returns the messages, the deposited amount after tax and the minted amount.
+pub fn deposit_stable(
+    mut deps: DepsMut,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint256,
+) -> Result<(Vec<CosmosMsg>, Uint256, Uint256), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    record_rate_snapshot(deps.branch(), env.block.time.seconds())?;
+
     let deposit_coin = deduct_tax(
         deps.as_ref(),
         Coin {
             denom: config.stable_denom.clone(),
-            amount: deposit_amount.into(),
+            amount: amount.into(),
         },
     )?;
-    deposit_amount = Uint256::from(deposit_coin.amount);
+    let deposit_amount = Uint256::from(deposit_coin.amount);
 
     // Cannot deposit smallish amount
-    if deposit_amount <= Uint256::from(1_000_000u128) {
-        return Err(StdError::generic_err(format!(
-            "Deposit amount must be greater than 1 UST {}",
-            config.stable_denom,
-        )));
+    if deposit_amount <= config.min_deposit {
+        return Err(ContractError::DepositTooSmall {
+            min: config.min_deposit,
+            denom: config.stable_denom,
+        });
     }
 
-    let capa_exchange_rate: Decimal256 = query_capapult_exchange_rate(deps.as_ref())?;
+    let capa_exchange_rate: Decimal256 =
+        query_capapult_exchange_rate(deps.as_ref(), env.block.time.seconds())?;
     let mint_amount = deposit_amount / capa_exchange_rate;
 
-    let sender: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let mut current_deposit = read_total_deposit(deps.storage, &sender);
+    let recipient_canon: CanonicalAddr = deps.api.addr_canonicalize(recipient.as_str())?;
+    let mut current_deposit = read_total_deposit(deps.storage, &recipient_canon);
     current_deposit += deposit_amount;
-    store_total_deposit(deps.storage, &sender, &current_deposit)?;
+    store_total_deposit(deps.storage, &recipient_canon, &current_deposit)?;
 
-    let mut last_ops_ust = read_last_ops_ust(deps.storage, &sender, Uint256::zero());
+    let mut last_ops_ust = read_last_ops_ust(deps.storage, &recipient_canon, Uint256::zero());
     last_ops_ust += deposit_amount;
-    store_last_ops_ust(deps.storage, &sender, &last_ops_ust)?;
+    store_last_ops_ust(deps.storage, &recipient_canon, &last_ops_ust)?;
 
-    Ok(Response::new()
-        .add_messages(vec![
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
-                msg: to_binary(&DepositStableHandleMsg::DepositStable {})?,
-                funds: vec![Coin {
-                    denom: config.stable_denom.clone(),
-                    amount: deposit_amount.into(),
-                }],
-            }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.cterra_contract)?.to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Mint {
-                    recipient: info.sender.to_string(),
-                    amount: mint_amount.into(),
-                })?,
-            }),
-        ])
-        .add_attributes(vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", info.sender),
-            attr("mint_amount", mint_amount),
-            attr("deposit_amount", deposit_amount),
-        ]))
+    append_history(
+        deps.storage,
+        &recipient_canon,
+        &HistoryEntry {
+            op: AccountOp::Deposit,
+            time: env.block.time.seconds(),
+            ust_amount: deposit_amount,
+            cust_amount: mint_amount,
+            exchange_rate: capa_exchange_rate,
+        },
+        MAX_HISTORY_ENTRIES,
+    )?;
+
+    let messages = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+            msg: to_binary(&DepositStableHandleMsg::DepositStable {})?,
+            funds: vec![Coin {
+                denom: config.stable_denom.clone(),
+                amount: deposit_amount.into(),
+            }],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.cterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount: mint_amount.into(),
+            })?,
+        }),
+    ];
+    Ok((messages, deposit_amount, mint_amount))
 }
 
 pub fn redeem_stable(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     sender: Addr,
     burn_amount: Uint128,
-) -> StdResult<Response> {
+    recipient: Option<String>,
+    min_receive: Option<Uint128>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => sender.clone(),
+    };
+    record_rate_snapshot(deps.branch(), env.block.time.seconds())?;
     // Load anchor token exchange rate with updated state
-    let capa_exchange_rate: Decimal256 = query_capapult_exchange_rate(deps.as_ref())?;
+    let capa_exchange_rate: Decimal256 =
+        query_capapult_exchange_rate(deps.as_ref(), env.block.time.seconds())?;
     let exchange_rate: Decimal256 = query_exchange_rate(deps.as_ref())?;
 
     let mut withdraw_amount = Uint256::from(burn_amount) * capa_exchange_rate;
@@ -112,18 +169,34 @@ pub fn redeem_stable(
     )?;
     withdraw_amount = withdraw_amount - tax_amount;
 
-    if withdraw_amount <= Uint256::from(1_000_000u128) {
-        return Err(StdError::generic_err(format!(
-            "Withdrawal amount must be greater than 1 UST {}",
-            config.stable_denom,
-        )));
+    if withdraw_amount <= config.min_withdraw {
+        return Err(ContractError::WithdrawTooSmall {
+            min: config.min_withdraw,
+            denom: config.stable_denom,
+        });
+    }
+
+    if let Some(min_receive) = min_receive {
+        if withdraw_amount < Uint256::from(min_receive) {
+            return Err(ContractError::RedeemBelowMinimum {
+                amount: withdraw_amount,
+                min_receive,
+            });
+        }
     }
 
     let aust_burn_amount = withdraw_amount / exchange_rate;
     let aust_contract_address = deps.api.addr_humanize(&config.aterra_contract)?;
 
+    // unclaimed profit and dust belong to capacorp holders
     let current_balance =
         query_token_balance(deps.as_ref(), &aust_contract_address, &env.contract.address)?;
+    let reserved_profit = read_reserved_profit(deps.storage)?;
+    let current_balance = if current_balance > reserved_profit {
+        current_balance - reserved_profit
+    } else {
+        Uint256::zero()
+    };
 
     let cust_balance = query_token_balance(
         deps.as_ref(),
@@ -132,10 +205,10 @@ pub fn redeem_stable(
     )?;
 
     if aust_burn_amount > current_balance {
-        return Err(StdError::generic_err(format!(
-            "Not enough aust available; redeem amount {} larger than current balance {}",
-            aust_burn_amount, current_balance
-        )));
+        return Err(ContractError::InsufficientAust {
+            requested: aust_burn_amount,
+            available: current_balance,
+        });
     }
 
     let sender_canon: CanonicalAddr = deps.api.addr_canonicalize(sender.as_str())?;
@@ -176,6 +249,19 @@ pub fn redeem_stable(
     }
     store_last_ops_ust(deps.storage, &sender_canon, &last_ops_ust)?;
 
+    append_history(
+        deps.storage,
+        &sender_canon,
+        &HistoryEntry {
+            op: AccountOp::Redeem,
+            time: env.block.time.seconds(),
+            ust_amount: withdraw_amount,
+            cust_amount: Uint256::from(burn_amount),
+            exchange_rate: capa_exchange_rate,
+        },
+        MAX_HISTORY_ENTRIES,
+    )?;
+
     Ok(Response::new()
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
@@ -184,11 +270,11 @@ pub fn redeem_stable(
                 msg: to_binary(&Cw20ExecuteMsg::Send {
                     contract: deps.api.addr_humanize(&config.market_contract)?.to_string(),
                     amount: aust_burn_amount.into(),
-                    msg: to_binary(&RedeemStableHookMsg::RedeemStable {})?,
+                    msg: to_binary(&RedeemStableHandleMsg::RedeemStable {})?,
                 })?,
             }),
             CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.into(),
+                to_address: recipient.to_string(),
                 amount: vec![Coin {
                     denom: config.stable_denom.clone(),
                     amount: withdraw_amount.into(),
@@ -204,8 +290,254 @@ pub fn redeem_stable(
         ])
         .add_attributes(vec![
             attr("action", "redeem_stable"),
+            attr("recipient", recipient),
             attr("burn_amount cust", burn_amount),
             attr("aust_burn_amount aust", aust_burn_amount),
             attr("withdraw_amount ust", withdraw_amount),
         ]))
 }
+
+pub fn harvest(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    record_rate_snapshot(deps.branch(), env.block.time.seconds())?;
+    let capa_exchange_rate: Decimal256 =
+        query_capapult_exchange_rate(deps.as_ref(), env.block.time.seconds())?;
+    let exchange_rate: Decimal256 = query_exchange_rate(deps.as_ref())?;
+
+    let cust_balance = query_token_balance(
+        deps.as_ref(),
+        &deps.api.addr_humanize(&config.cterra_contract)?,
+        &info.sender,
+    )?;
+    let interest = query_harvest_value(
+        deps.as_ref(),
+        env.block.time.seconds(),
+        cust_balance,
+        info.sender.to_string(),
+    )?;
+
+    let harvest_amount = amount.unwrap_or(interest);
+    if harvest_amount > interest {
+        return Err(ContractError::HarvestTooLarge {
+            requested: harvest_amount,
+            available: interest,
+        });
+    }
+    if harvest_amount.is_zero() {
+        return Err(ContractError::NothingToHarvest {});
+    }
+
+    let burn_amount = harvest_amount / capa_exchange_rate;
+    // the cUST is burnt with BurnFrom, fail here rather than in the token
+    let allowance = query_token_allowance(
+        deps.as_ref(),
+        &env.block,
+        &deps.api.addr_humanize(&config.cterra_contract)?,
+        &info.sender,
+        &env.contract.address,
+    )?;
+    if allowance < burn_amount {
+        return Err(ContractError::InsufficientAllowance {
+            requested: burn_amount,
+            available: allowance,
+        });
+    }
+
+    let mut withdraw_amount = harvest_amount;
+    let tax_amount = compute_tax(
+        deps.as_ref(),
+        &Coin {
+            denom: config.stable_denom.clone(),
+            amount: withdraw_amount.into(),
+        },
+    )?;
+    withdraw_amount = withdraw_amount - tax_amount;
+    let tax_amount = compute_tax(
+        deps.as_ref(),
+        &Coin {
+            denom: config.stable_denom.clone(),
+            amount: withdraw_amount.into(),
+        },
+    )?;
+    withdraw_amount = withdraw_amount - tax_amount;
+
+    if withdraw_amount <= config.min_withdraw {
+        return Err(ContractError::WithdrawTooSmall {
+            min: config.min_withdraw,
+            denom: config.stable_denom,
+        });
+    }
+
+    let aust_burn_amount = withdraw_amount / exchange_rate;
+    let current_balance = query_token_balance(
+        deps.as_ref(),
+        &deps.api.addr_humanize(&config.aterra_contract)?,
+        &env.contract.address,
+    )?;
+    let reserved_profit = read_reserved_profit(deps.storage)?;
+    let current_balance = if current_balance > reserved_profit {
+        current_balance - reserved_profit
+    } else {
+        Uint256::zero()
+    };
+    if aust_burn_amount > current_balance {
+        return Err(ContractError::InsufficientAust {
+            requested: aust_burn_amount,
+            available: current_balance,
+        });
+    }
+
+    let sender_canon: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut user_claim = read_total_claim(deps.storage, &sender_canon);
+    user_claim += harvest_amount;
+    store_total_claim(deps.storage, &sender_canon, &user_claim)?;
+
+    // principal stays deposited, reset its basis to what the remaining cUST is worth
+    let remaining_ust = (cust_balance - burn_amount) * capa_exchange_rate;
+    let remaining_interest = interest - harvest_amount;
+    let last_ops_ust = if remaining_ust > remaining_interest {
+        remaining_ust - remaining_interest
+    } else {
+        Uint256::zero()
+    };
+    store_last_ops_ust(deps.storage, &sender_canon, &last_ops_ust)?;
+
+    append_history(
+        deps.storage,
+        &sender_canon,
+        &HistoryEntry {
+            op: AccountOp::Harvest,
+            time: env.block.time.seconds(),
+            ust_amount: withdraw_amount,
+            cust_amount: burn_amount,
+            exchange_rate: capa_exchange_rate,
+        },
+        MAX_HISTORY_ENTRIES,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.cterra_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::BurnFrom {
+                    owner: info.sender.to_string(),
+                    amount: burn_amount.into(),
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+                    amount: aust_burn_amount.into(),
+                    msg: to_binary(&RedeemStableHandleMsg::RedeemStable {})?,
+                })?,
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom: config.stable_denom.clone(),
+                    amount: withdraw_amount.into(),
+                }],
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "harvest"),
+            attr("harvester", info.sender),
+            attr("burn_amount", burn_amount),
+            attr("harvest_amount", harvest_amount),
+            attr("withdraw_amount", withdraw_amount),
+        ]))
+}
+
+/// Moves the share of the sender's deposited basis matching the cUST it transferred
+/// over to the recipient, so both keep harvesting only their own interest
+pub fn balance_moved(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.cterra_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // cUST sent to this contract is accounted for by the operation it is sent for
+    if recipient == env.contract.address.as_str() {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "balance_moved"),
+            attr("sender", sender),
+            attr("recipient", recipient),
+            attr("amount", amount),
+        ]));
+    }
+
+    let sender_addr = deps.api.addr_validate(&sender)?;
+    let sender_canon = deps.api.addr_canonicalize(&sender)?;
+    let recipient_canon = deps.api.addr_canonicalize(&recipient)?;
+    let amount = Uint256::from(amount);
+
+    // cUST balances are already updated, the sender held balance + amount before the move
+    let balance = query_token_balance(deps.as_ref(), &info.sender, &sender_addr)?;
+    let share = Decimal256::from_uint256(amount) / Decimal256::from_uint256(balance + amount);
+
+    let last_ops_ust = read_last_ops_ust(deps.storage, &sender_canon, Uint256::zero());
+    let total_deposit = read_total_deposit(deps.storage, &sender_canon);
+    let (moved_ops_ust, moved_deposit) = if balance.is_zero() {
+        (last_ops_ust, total_deposit)
+    } else {
+        (last_ops_ust * share, total_deposit * share)
+    };
+    store_last_ops_ust(deps.storage, &sender_canon, &(last_ops_ust - moved_ops_ust))?;
+    store_total_deposit(
+        deps.storage,
+        &sender_canon,
+        &(total_deposit - moved_deposit),
+    )?;
+
+    let mut last_ops_ust = read_last_ops_ust(deps.storage, &recipient_canon, Uint256::zero());
+    last_ops_ust += moved_ops_ust;
+    store_last_ops_ust(deps.storage, &recipient_canon, &last_ops_ust)?;
+    let mut total_deposit = read_total_deposit(deps.storage, &recipient_canon);
+    total_deposit += moved_deposit;
+    store_total_deposit(deps.storage, &recipient_canon, &total_deposit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "balance_moved"),
+        attr("sender", sender),
+        attr("recipient", recipient),
+        attr("amount", amount),
+        attr("moved_ops_ust", moved_ops_ust),
+        attr("moved_deposit", moved_deposit),
+    ]))
+}
+
+/// Stores the aUST and cUST exchange rates unless the period of block_time
+/// already has a snapshot
+pub fn record_rate_snapshot(deps: DepsMut, block_time: u64) -> StdResult<()> {
+    let period = block_time / RATE_SNAPSHOT_PERIOD;
+    if read_rate_snapshot(deps.storage, period)?.is_some() {
+        return Ok(());
+    }
+
+    let aterra_rate = query_exchange_rate(deps.as_ref())?;
+    let capa_yield = query_capa_yield(deps.as_ref(), block_time)?;
+    store_rate_snapshot(
+        deps.storage,
+        period,
+        &RateSnapshot {
+            time: block_time,
+            aterra_rate,
+            capa_rate: ExchangeRate::capapult_exchange_rate(aterra_rate, capa_yield)?,
+        },
+    )
+}
@@ -0,0 +1,138 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Must deposit initial funds {amount}{denom}")]
+    InvalidInitialDeposit { amount: Uint128, denom: String },
+
+    #[error("Owner address does not pass validation")]
+    InvalidOwnerAddress {},
+
+    #[error("Deposits are paused")]
+    DepositPaused {},
+
+    #[error("Redemptions are paused")]
+    RedeemPaused {},
+
+    #[error("Distribution is paused")]
+    DistributePaused {},
+
+    #[error("Invalid capa_yield {capa_yield}: must be greater than 0 and at most 1")]
+    InvalidCapaYield { capa_yield: Decimal256 },
+
+    #[error("Invalid legacy capa_yield {capa_yield}: must be an integer percentage")]
+    InvalidLegacyCapaYield { capa_yield: String },
+
+    #[error("Cannot migrate from {contract} {version}")]
+    UnsupportedMigration { contract: String, version: String },
+
+    #[error("Insurance share must not exceed {max}")]
+    InsuranceShareTooHigh { max: Decimal256 },
+
+    #[error("Yield ramp must not last longer than {max} seconds")]
+    YieldRampTooLong { max: u64 },
+
+    #[error("No ownership proposal pending")]
+    NoPendingOwner {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Contracts are already registered")]
+    ContractsAlreadyRegistered {},
+
+    #[error("Contracts are not registered yet")]
+    ContractsNotRegistered {},
+
+    #[error("{addr} is not a valid {kind} contract")]
+    InvalidContract { kind: String, addr: String },
+
+    #[error("Deposit amount must be greater than {min}{denom}")]
+    DepositTooSmall { min: Uint256, denom: String },
+
+    #[error("Withdrawal amount must be greater than {min}{denom}")]
+    WithdrawTooSmall { min: Uint256, denom: String },
+
+    #[error("Redeemed amount {amount} is below the minimum {min_receive}")]
+    RedeemBelowMinimum { amount: Uint256, min_receive: Uint128 },
+
+    #[error("No interest to harvest")]
+    NothingToHarvest {},
+
+    #[error("Harvest amount {requested} larger than accrued interest {available}")]
+    HarvestTooLarge {
+        requested: Uint256,
+        available: Uint256,
+    },
+
+    #[error("Not enough aust available; redeem amount {requested} larger than current balance {available}")]
+    InsufficientAust {
+        requested: Uint256,
+        available: Uint256,
+    },
+
+    #[error("Too little profit to distribute: {profit}, minimum is {min}")]
+    ProfitBelowThreshold { profit: Uint256, min: Uint256 },
+
+    #[error("Minimum amounts must be greater than zero")]
+    InvalidMinimum {},
+
+    #[error("No capacorp supply to distribute profit to")]
+    NoCapacorpSupply {},
+
+    #[error("Capacorp does not report its transfers to this contract")]
+    CapacorpHookNotRegistered {},
+
+    #[error("No profit to claim")]
+    NothingToClaim {},
+
+    #[error("Keeper bounty must be at most {max}")]
+    KeeperBountyTooHigh { max: Decimal256 },
+
+    #[error("Keeper interval must be greater than 0 while keeper mode is enabled")]
+    InvalidKeeperInterval {},
+
+    #[error("Keeper interval must not be longer than {max} seconds")]
+    KeeperIntervalTooLong { max: u64 },
+
+    #[error("Distribution not allowed before {next_time}")]
+    DistributeTooEarly { next_time: u64 },
+
+    #[error("Reserved profit {reserved} exceeds the aUST balance {balance}")]
+    ReservedProfitExceedsBalance { reserved: Uint256, balance: Uint256 },
+
+    #[error("No {denom} sent")]
+    NoFunds { denom: String },
+
+    #[error("cUST allowance {available} smaller than harvest burn amount {requested}")]
+    InsufficientAllowance {
+        requested: Uint256,
+        available: Uint256,
+    },
+
+    #[error("Escrow amount {requested} larger than escrow balance {available}")]
+    InsufficientEscrow {
+        requested: Uint256,
+        available: Uint256,
+    },
+
+    #[error("Order interval must be greater than zero")]
+    InvalidOrderInterval {},
+
+    #[error("Order interval must not be longer than {max} seconds")]
+    OrderIntervalTooLong { max: u64 },
+
+    #[error("At most {max} orders per user")]
+    TooManyOrders { max: u32 },
+
+    #[error("Order {id} not found")]
+    OrderNotFound { id: u64 },
+}
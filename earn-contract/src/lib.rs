@@ -1,9 +1,14 @@
 pub mod contract;
 pub mod deposit;
+mod error;
 pub mod math;
+mod migrations;
 pub mod msg;
+pub mod orders;
 pub mod querier;
 pub mod state;
 
+pub use crate::error::ContractError;
+
 #[cfg(test)]
 mod testing;
@@ -184,7 +184,7 @@ pub trait Calculate {
 
     fn capapult_exchange_rate(
         a_terra_exchange_rate: Decimal256,
-        yield_user: String,
+        yield_user: Decimal256,
     ) -> StdResult<Decimal256>;
 }
 
@@ -213,16 +213,9 @@ impl Calculate for ExchangeRate {
 
     fn capapult_exchange_rate(
         a_terra_exchange_rate: Decimal256,
-        yield_user: String,
+        yield_user: Decimal256,
     ) -> StdResult<Decimal256> {
-        let yield_int: i32;
-        match yield_user.parse::<i32>() {
-            Ok(n) => yield_int = n,
-            Err(_e) => yield_int = 55,
-        };
-
-        let rate = a_terra_exchange_rate
-            * Decimal256::from_ratio(U256::from(yield_int), U256::from(100i32));
+        let rate = a_terra_exchange_rate * yield_user;
         Ok(rate)
     }
 }
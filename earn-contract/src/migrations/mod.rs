@@ -0,0 +1,5 @@
+mod v04;
+
+pub use v04::migrate_v04_config;
+#[cfg(test)]
+pub use v04::testing::generate_v04_test_config;
@@ -0,0 +1,91 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{CanonicalAddr, Storage};
+use cosmwasm_storage::ReadonlySingleton;
+
+use crate::contract::{validate_capa_yield, _1M_, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT};
+use crate::error::ContractError;
+use crate::state::{store_config, Config, KEY_CONFIG};
+
+/// this takes a v0.4.x config and converts it to the current format
+pub fn migrate_v04_config(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let old: OldConfig = ReadonlySingleton::new(storage, KEY_CONFIG).load()?;
+
+    // v0.4.x read the yield as an integer percentage, refuse anything else
+    // rather than guessing what the misconfigured value was meant to be
+    let capa_yield = match old.capa_yield.parse::<u64>() {
+        Ok(n) => Decimal256::percent(n),
+        Err(_e) => {
+            return Err(ContractError::InvalidLegacyCapaYield {
+                capa_yield: old.capa_yield,
+            })
+        }
+    };
+    validate_capa_yield(capa_yield)?;
+
+    let config = Config {
+        contract_addr: old.contract_addr,
+        guardian_addr: old.owner_addr.clone(),
+        owner_addr: old.owner_addr,
+        market_contract: old.market_contract,
+        aterra_contract: old.aterra_contract,
+        cterra_contract: old.cterra_contract,
+        capacorp_contract: old.capacorp_contract,
+        capa_contract: old.capa_contract,
+        insurance_contract: old.insurance_contract,
+        stable_denom: old.stable_denom,
+        capa_yield,
+        insurance_share: Decimal256::zero(),
+        min_deposit: Uint256::from(_1M_),
+        min_withdraw: Uint256::from(_1M_),
+        min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        deposit_paused: false,
+        redeem_paused: false,
+        distribute_paused: false,
+        keeper_enabled: false,
+        keeper_interval: DEFAULT_KEEPER_INTERVAL,
+        keeper_bounty: Decimal256::zero(),
+    };
+    store_config(storage, &config)?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OldConfig {
+    pub contract_addr: CanonicalAddr,
+    pub owner_addr: CanonicalAddr,
+    pub market_contract: CanonicalAddr,
+    pub aterra_contract: CanonicalAddr,
+    pub cterra_contract: CanonicalAddr,
+    pub capacorp_contract: CanonicalAddr,
+    pub capa_contract: CanonicalAddr,
+    pub insurance_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub capa_yield: String,
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use cosmwasm_storage::Singleton;
+
+    /// generates a v0.4.x config for the migration tests
+    pub fn generate_v04_test_config(storage: &mut dyn Storage, capa_yield: &str) {
+        let old = OldConfig {
+            contract_addr: CanonicalAddr::from(b"contract".to_vec()),
+            owner_addr: CanonicalAddr::from(b"owner".to_vec()),
+            market_contract: CanonicalAddr::from(b"market".to_vec()),
+            aterra_contract: CanonicalAddr::from(b"aterra".to_vec()),
+            cterra_contract: CanonicalAddr::from(b"cterra".to_vec()),
+            capacorp_contract: CanonicalAddr::from(b"capacorp".to_vec()),
+            capa_contract: CanonicalAddr::from(b"capa".to_vec()),
+            insurance_contract: CanonicalAddr::from(b"insurance".to_vec()),
+            stable_denom: "uusd".to_string(),
+            capa_yield: capa_yield.to_string(),
+        };
+        Singleton::new(storage, KEY_CONFIG).save(&old).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::Addr;
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{Addr, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,7 +10,8 @@ pub struct InstantiateMsg {
     pub owner_addr: String,
     /// stable coin denom used to borrow & repay
     pub stable_denom: String,
-    pub capa_yield: String,
+    /// share of the aUST yield paid to cUST holders, 0 < capa_yield <= 1
+    pub capa_yield: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -28,21 +29,125 @@ pub enum ExecuteMsg {
         capa_contract: String,
         insurance_contract: String,
     },
+    /// Replace registered contract addresses
+    UpdateContracts {
+        market_contract: Option<String>,
+        aterra_contract: Option<String>,
+        cterra_contract: Option<String>,
+        capacorp_contract: Option<String>,
+        capa_contract: Option<String>,
+        insurance_contract: Option<String>,
+    },
     /// Update config values
     UpdateConfig {
-        owner_addr: Option<Addr>,
+        guardian_addr: Option<Addr>,
+        insurance_share: Option<Decimal256>,
+        min_deposit: Option<Uint256>,
+        min_withdraw: Option<Uint256>,
+        min_distribute_profit: Option<Uint256>,
+        keeper_enabled: Option<bool>,
+        keeper_interval: Option<u64>,
+        keeper_bounty: Option<Decimal256>,
+    },
+    /// Move capa_yield linearly to target over duration seconds
+    UpdateYield {
+        target: Decimal256,
+        duration: u64,
+    },
+    /// Propose a new owner, who has to accept before the optional expiry
+    ProposeNewOwner {
+        owner_addr: String,
+        expires: Option<Expiration>,
+    },
+    /// Withdraw the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Accept a pending ownership proposal, only callable by the proposed owner
+    AcceptOwnership {},
+    /// Lift the pause on the selected operations
+    Unpause {
+        deposit: bool,
+        redeem: bool,
+        distribute: bool,
     },
     Distribute {},
+    /// Send the caller's accrued share of distributed profit, in aUST
+    ClaimProfit {},
     //  Fees {},
     ////////////////////
+    /// Guardian operations
+    ////////////////////
+    /// Halt the selected operations, also allowed to the owner
+    Pause {
+        deposit: bool,
+        redeem: bool,
+        distribute: bool,
+    },
+    ////////////////////
     /// User operations
     ////////////////////
-    /// Deposit stable asset to get interest
-    Deposit {},
+    /// Deposit stable asset to get interest, cUST is minted to recipient (default: sender)
+    Deposit {
+        recipient: Option<String>,
+    },
+    /// Burn the cUST worth the accrued interest (or amount of it) and pay it out in UST,
+    /// the sender must have granted this contract a cUST allowance
+    Harvest {
+        amount: Option<Uint256>,
+    },
+    /// Add the sent UST to the sender's escrow funding recurring deposits
+    FundEscrow {},
+    /// Send back amount (default: all) of the sender's escrow
+    WithdrawEscrow {
+        amount: Option<Uint256>,
+    },
+    /// Register a standing order run every interval seconds, first due right away
+    CreateOrder {
+        kind: OrderKind,
+        interval: u64,
+    },
+    /// Change an order of the sender
+    UpdateOrder {
+        id: u64,
+        kind: Option<OrderKind>,
+        interval: Option<u64>,
+    },
+    /// Delete an order of the sender
+    CancelOrder {
+        id: u64,
+    },
+    /// Run up to limit due orders, callable by anyone
+    ExecuteOrders {
+        limit: Option<u32>,
+    },
     RemoveAccount {
         addr: Option<Addr>,
     },
     Receive(Cw20ReceiveMsg),
+    ////////////////////
+    /// cUST and capacorp operations
+    ////////////////////
+    /// Sent by cUST or capacorp after amount moved from sender to recipient,
+    /// including moves to this contract
+    BalanceMoved {
+        sender: String,
+        recipient: String,
+        amount: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    /// Deposit amount UST taken from the owner's escrow
+    RecurringDeposit { amount: Uint256 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountOp {
+    Deposit,
+    Redeem,
+    Harvest,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -51,15 +156,38 @@ pub enum DepositStableHandleMsg {
     DepositStable {},
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RedeemStableHandleMsg {
+    RedeemStable {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountingHookQueryMsg {
+    AccountingHook {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AccountingHookResponse {
+    pub contract: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RedeemStableHookMsg {
-    RedeemStable {},
+    /// Burn the received cUST and send UST to recipient (default: sender),
+    /// failing if less than min_receive is paid out
+    RedeemStable {
+        recipient: Option<String>,
+        min_receive: Option<Uint128>,
+    },
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
     ExchangeRate {},
     Dashboard {},
     CorpAccounts {},
@@ -77,6 +205,48 @@ pub enum QueryMsg {
         contract_addr: String,
     },
     QueryCapapultRate {},
+    YieldSchedule {},
+    PendingProfit {
+        address: String,
+    },
+    ClaimedProfit {
+        address: String,
+    },
+    DistributionSchedule {},
+    UserOrders {
+        address: String,
+    },
+    /// Latest operations of address, oldest first
+    AccountHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Rate snapshots taken after start_after (a snapshot time), oldest first
+    RateHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Annualized growth of the rates over the last window_seconds of snapshots
+    RealizedApy {
+        window_seconds: u64,
+    },
+    /// Expected UST value of the cUST held by address in days
+    ProjectBalance {
+        address: String,
+        days: u64,
+    },
+    /// Expected UST value in days of depositing amount now
+    ProjectDeposit {
+        amount: Uint256,
+        days: u64,
+    },
+    /// Annualized yield expected from the current rates
+    ImpliedApy {},
+    /// Position of address and the rates it is valued at
+    AccountSummary {
+        address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -88,13 +258,23 @@ pub enum QueryStateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Account {
-    AllAccounts {},
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStatsMsg {
+    TokenStats {},
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner_addr: String,
+    pub guardian_addr: String,
     pub market_contract: String,
     pub aterra_contract: String,
     pub cterra_contract: String,
@@ -102,7 +282,118 @@ pub struct ConfigResponse {
     pub capa_contract: String,
     pub insurance_contract: String,
     pub stable_denom: String,
-    pub capa_yield: String,
+    pub capa_yield: Decimal256,
+    pub insurance_share: Decimal256,
+    pub min_deposit: Uint256,
+    pub min_withdraw: Uint256,
+    pub min_distribute_profit: Uint256,
+    pub deposit_paused: bool,
+    pub redeem_paused: bool,
+    pub distribute_paused: bool,
+    pub keeper_enabled: bool,
+    pub keeper_interval: u64,
+    pub keeper_bounty: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub owner_addr: String,
+    pub expires: Expiration,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct YieldScheduleResponse {
+    pub current_yield: Decimal256,
+    pub start_yield: Decimal256,
+    pub target_yield: Decimal256,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributionScheduleResponse {
+    pub keeper_enabled: bool,
+    pub last_distribution_time: u64,
+    pub next_distribution_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OrderResponse {
+    pub id: u64,
+    pub kind: OrderKind,
+    pub interval: u64,
+    pub next_execution: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserOrdersResponse {
+    pub escrow: Uint256,
+    pub orders: Vec<OrderResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryEntryResponse {
+    pub id: u64,
+    pub op: AccountOp,
+    pub time: u64,
+    pub ust_amount: Uint256,
+    pub cust_amount: Uint256,
+    /// cUST exchange rate of the operation
+    pub exchange_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountHistoryResponse {
+    pub entries: Vec<HistoryEntryResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSnapshotResponse {
+    pub time: u64,
+    pub aterra_rate: Decimal256,
+    pub capa_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateHistoryResponse {
+    pub snapshots: Vec<RateSnapshotResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RealizedApyResponse {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub aterra_apy: Decimal256,
+    pub capa_apy: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProjectionResponse {
+    pub cust_amount: Uint256,
+    pub current_value: Uint256,
+    pub projected_value: Uint256,
+    pub projected_interest: Uint256,
+    pub projected_exchange_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ImpliedApyResponse {
+    pub aterra_apy: Decimal256,
+    pub capa_apy: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountSummaryResponse {
+    pub cust_balance: Uint256,
+    pub ust_value: Uint256,
+    pub total_deposit: Uint256,
+    pub last_ops_ust: Uint256,
+    pub available_harvest: Uint256,
+    pub harvested_sum: Uint256,
+    pub exchange_rate: Decimal256,
+    pub capa_exchange_rate: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -119,6 +410,12 @@ pub struct MarketStateResponse {
     pub prev_exchange_rate: Decimal256,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenStatsResponse {
+    pub total_supply: Uint256,
+    pub holder_count: u64,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct DashboardResponse {
@@ -128,6 +425,7 @@ pub struct DashboardResponse {
     pub cust_avg_balance: Uint256,
     pub current_profit: Uint256,
     pub total_profit: Uint256,
+    pub total_insurance: Uint256,
 }
 /// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
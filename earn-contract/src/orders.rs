@@ -0,0 +1,259 @@
+use crate::deposit::deposit_stable;
+use crate::error::ContractError;
+use crate::msg::OrderKind;
+use crate::querier::deduct_tax;
+use crate::state::{
+    next_order_id, read_config, read_escrow, read_order, read_order_cursor, read_orders,
+    read_user_order_ids, remove_order, store_escrow, store_order, store_order_cursor, Config,
+    Order,
+};
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, Addr, Attribute, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response,
+};
+
+pub const MAX_ORDERS_PER_USER: u32 = 10;
+const DEFAULT_EXECUTE_LIMIT: u32 = 10;
+const MAX_EXECUTE_LIMIT: u32 = 30;
+/// Longest order interval, in seconds (one year)
+pub const MAX_ORDER_INTERVAL: u64 = 365 * 86400;
+
+pub fn fund_escrow(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if amount.is_zero() {
+        return Err(ContractError::NoFunds {
+            denom: config.stable_denom,
+        });
+    }
+
+    let sender_canon = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let escrow = read_escrow(deps.storage, &sender_canon) + amount;
+    store_escrow(deps.storage, &sender_canon, &escrow)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "fund_escrow"),
+        attr("owner", info.sender),
+        attr("amount", amount),
+        attr("escrow", escrow),
+    ]))
+}
+
+pub fn withdraw_escrow(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_canon = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let escrow = read_escrow(deps.storage, &sender_canon);
+    let amount = amount.unwrap_or(escrow);
+    if amount.is_zero() || amount > escrow {
+        return Err(ContractError::InsufficientEscrow {
+            requested: amount,
+            available: escrow,
+        });
+    }
+    store_escrow(deps.storage, &sender_canon, &(escrow - amount))?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: amount.into(),
+                },
+            )?],
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw_escrow"),
+            attr("owner", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn create_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    kind: OrderKind,
+    interval: u64,
+) -> Result<Response, ContractError> {
+    validate_order(&read_config(deps.storage)?, &kind, interval)?;
+
+    let owner = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_user_order_ids(deps.storage, &owner)?.len() >= MAX_ORDERS_PER_USER as usize {
+        return Err(ContractError::TooManyOrders {
+            max: MAX_ORDERS_PER_USER,
+        });
+    }
+
+    let id = next_order_id(deps.storage)?;
+    store_order(
+        deps.storage,
+        id,
+        &Order {
+            owner,
+            kind,
+            interval,
+            next_execution: env.block.time.seconds(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_order"),
+        attr("owner", info.sender),
+        attr("order_id", id.to_string()),
+    ]))
+}
+
+pub fn update_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+    kind: Option<OrderKind>,
+    interval: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut order = read_owned_order(&deps, &info.sender, id)?;
+    if let Some(kind) = kind {
+        order.kind = kind;
+    }
+    if let Some(interval) = interval {
+        order.interval = interval;
+    }
+    validate_order(&read_config(deps.storage)?, &order.kind, order.interval)?;
+    store_order(deps.storage, id, &order)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_order"),
+        attr("owner", info.sender),
+        attr("order_id", id.to_string()),
+    ]))
+}
+
+pub fn cancel_order(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    let order = read_owned_order(&deps, &info.sender, id)?;
+    remove_order(deps.storage, id, &order.owner);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_order"),
+        attr("owner", info.sender),
+        attr("order_id", id.to_string()),
+    ]))
+}
+
+/// Looks at up to limit orders after the last one seen by the previous call,
+/// wrapping around to the first order, and runs those that are due.
+/// An order that cannot run is skipped and stays due.
+pub fn execute_orders(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_EXECUTE_LIMIT)
+        .min(MAX_EXECUTE_LIMIT) as usize;
+    let cursor = read_order_cursor(deps.storage)?;
+
+    let mut orders = read_orders(deps.storage, cursor, limit)?;
+    if let Some(cursor) = cursor {
+        if orders.len() < limit {
+            let wrapped = read_orders(deps.storage, None, limit - orders.len())?;
+            orders.extend(wrapped.into_iter().filter(|(id, _)| *id <= cursor));
+        }
+    }
+
+    let now = env.block.time.seconds();
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut executed: Vec<String> = vec![];
+    let mut skipped: Vec<String> = vec![];
+    let mut errors: Vec<Attribute> = vec![];
+    for (id, mut order) in orders.iter().cloned() {
+        if order.next_execution > now {
+            continue;
+        }
+        match execute_order(deps.branch(), &env, &order) {
+            Ok(mut msgs) => {
+                messages.append(&mut msgs);
+                order.next_execution = now + order.interval;
+                store_order(deps.storage, id, &order)?;
+                executed.push(id.to_string());
+            }
+            Err(err) => {
+                skipped.push(id.to_string());
+                errors.push(attr("order_error", format!("{}: {}", id, err)));
+            }
+        }
+    }
+    if let Some((id, _)) = orders.last() {
+        store_order_cursor(deps.storage, *id)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "execute_orders"),
+            attr("executed", executed.join(",")),
+            attr("skipped", skipped.join(",")),
+        ])
+        .add_attributes(errors))
+}
+
+fn execute_order(
+    mut deps: DepsMut,
+    env: &Env,
+    order: &Order,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let owner = deps.api.addr_humanize(&order.owner)?;
+    match &order.kind {
+        OrderKind::RecurringDeposit { amount } => {
+            let escrow = read_escrow(deps.storage, &order.owner);
+            if *amount > escrow {
+                return Err(ContractError::InsufficientEscrow {
+                    requested: *amount,
+                    available: escrow,
+                });
+            }
+            let (messages, _, _) = deposit_stable(deps.branch(), env, &owner, *amount)?;
+            store_escrow(deps.storage, &order.owner, &(escrow - *amount))?;
+            Ok(messages)
+        }
+    }
+}
+
+fn read_owned_order(deps: &DepsMut, sender: &Addr, id: u64) -> Result<Order, ContractError> {
+    let order = read_order(deps.storage, id)?.ok_or(ContractError::OrderNotFound { id })?;
+    if order.owner != deps.api.addr_canonicalize(sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(order)
+}
+
+fn validate_order(config: &Config, kind: &OrderKind, interval: u64) -> Result<(), ContractError> {
+    if interval == 0 {
+        return Err(ContractError::InvalidOrderInterval {});
+    }
+    if interval > MAX_ORDER_INTERVAL {
+        return Err(ContractError::OrderIntervalTooLong {
+            max: MAX_ORDER_INTERVAL,
+        });
+    }
+    match kind {
+        OrderKind::RecurringDeposit { amount } => {
+            if *amount <= config.min_deposit {
+                return Err(ContractError::DepositTooSmall {
+                    min: config.min_deposit,
+                    denom: config.stable_denom.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,18 +1,41 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 
+use crate::deposit::RATE_SNAPSHOT_PERIOD;
 use crate::math::*;
-use crate::msg::{Account, ConfigResponse, DashboardResponse, MarketStateResponse, QueryStateMsg};
-use crate::state::{read_config, read_last_ops_ust, read_profit, read_total_claim, Config};
+use crate::msg::{
+    Account, AccountHistoryResponse, AccountSummaryResponse, AccountingHookQueryMsg,
+    AccountingHookResponse, ConfigResponse, DashboardResponse, DistributionScheduleResponse,
+    HistoryEntryResponse, ImpliedApyResponse, MarketStateResponse, OrderResponse,
+    PendingOwnerResponse, ProjectionResponse, QueryStateMsg, RateHistoryResponse,
+    RateSnapshotResponse, RealizedApyResponse, TokenStatsMsg, TokenStatsResponse,
+    UserOrdersResponse, YieldScheduleResponse,
+};
+use crate::state::{
+    read_config, read_escrow, read_history, read_holder_info, read_insurance,
+    read_last_distribution, read_last_ops_ust, read_last_rate_snapshot, read_order,
+    read_pending_owner, read_profit, read_profit_index, read_rate_snapshots, read_reserved_profit,
+    read_total_claim, read_total_deposit, read_user_order_ids, read_yield_schedule, Config,
+    HolderInfo,
+};
 use cw20::{
-    AllAccountsResponse, BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse,
+    AllAccountsResponse, AllowanceResponse, BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg,
+    TokenInfoResponse,
 };
 
 use cosmwasm_std::{
-    to_binary, Addr, CanonicalAddr, Coin, Deps, QueryRequest, StdResult, WasmQuery,
+    to_binary, Addr, BlockInfo, CanonicalAddr, Coin, Deps, QueryRequest, StdError, StdResult,
+    WasmQuery,
 };
 
 use terra_cosmwasm::TerraQuerier;
 
+pub fn query_market_state(deps: Deps, market_addr: Addr) -> StdResult<MarketStateResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: market_addr.to_string(),
+        msg: to_binary(&QueryStateMsg::State {})?,
+    }))
+}
+
 pub fn query_exchange_rate(deps: Deps) -> StdResult<Decimal256> {
     let config: Config = read_config(deps.storage)?;
     let market_state: StdResult<MarketStateResponse> =
@@ -25,8 +48,37 @@ pub fn query_exchange_rate(deps: Deps) -> StdResult<Decimal256> {
     Ok(prev_exchange_rate)
 }
 
-pub fn query_capapult_exchange_rate(deps: Deps) -> StdResult<Decimal256> {
+/// Effective capa_yield at block_time, following the pending yield schedule if any
+pub fn query_capa_yield(deps: Deps, block_time: u64) -> StdResult<Decimal256> {
+    match read_yield_schedule(deps.storage)? {
+        Some(schedule) => Ok(schedule.yield_at(block_time)),
+        None => Ok(read_config(deps.storage)?.capa_yield),
+    }
+}
+
+pub fn query_yield_schedule(deps: Deps, block_time: u64) -> StdResult<YieldScheduleResponse> {
+    let current_yield = query_capa_yield(deps, block_time)?;
+    match read_yield_schedule(deps.storage)? {
+        Some(schedule) => Ok(YieldScheduleResponse {
+            current_yield,
+            start_yield: schedule.start_yield,
+            target_yield: schedule.target_yield,
+            start_time: schedule.start_time,
+            end_time: schedule.end_time,
+        }),
+        None => Ok(YieldScheduleResponse {
+            current_yield,
+            start_yield: current_yield,
+            target_yield: current_yield,
+            start_time: 0,
+            end_time: 0,
+        }),
+    }
+}
+
+pub fn query_capapult_exchange_rate(deps: Deps, block_time: u64) -> StdResult<Decimal256> {
     let config: Config = read_config(deps.storage)?;
+    let capa_yield = query_capa_yield(deps, block_time)?;
     let market_state: StdResult<MarketStateResponse> =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
@@ -34,7 +86,7 @@ pub fn query_capapult_exchange_rate(deps: Deps) -> StdResult<Decimal256> {
         }));
 
     let exchange_rate =
-        ExchangeRate::capapult_exchange_rate(market_state?.prev_exchange_rate, config.capa_yield)?;
+        ExchangeRate::capapult_exchange_rate(market_state?.prev_exchange_rate, capa_yield)?;
     Ok(exchange_rate)
 }
 
@@ -53,10 +105,32 @@ pub fn query_token_balance(
     Ok(Uint256::from(res.balance))
 }
 
+/// Amount spender may still take from owner, zero once the allowance expired
+pub fn query_token_allowance(
+    deps: Deps,
+    block: &BlockInfo,
+    contract_addr: &Addr,
+    owner: &Addr,
+    spender: &Addr,
+) -> StdResult<Uint256> {
+    let res: AllowanceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::Allowance {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+        })?,
+    }))?;
+    if res.expires.is_expired(block) {
+        return Ok(Uint256::zero());
+    }
+    Ok(Uint256::from(res.allowance))
+}
+
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config: Config = read_config(deps.storage)?;
     Ok(ConfigResponse {
         owner_addr: deps.api.addr_humanize(&config.owner_addr)?.to_string(),
+        guardian_addr: deps.api.addr_humanize(&config.guardian_addr)?.to_string(),
         market_contract: deps.api.addr_humanize(&config.market_contract)?.to_string(),
         aterra_contract: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
         cterra_contract: deps.api.addr_humanize(&config.cterra_contract)?.to_string(),
@@ -71,9 +145,319 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         capa_yield: config.capa_yield,
+        insurance_share: config.insurance_share,
+        min_deposit: config.min_deposit,
+        min_withdraw: config.min_withdraw,
+        min_distribute_profit: config.min_distribute_profit,
+        deposit_paused: config.deposit_paused,
+        redeem_paused: config.redeem_paused,
+        distribute_paused: config.distribute_paused,
+        keeper_enabled: config.keeper_enabled,
+        keeper_interval: config.keeper_interval,
+        keeper_bounty: config.keeper_bounty,
+    })
+}
+
+/// Holder info settled up to the current profit index, using the live capacorp balance
+pub fn compute_holder_profit(deps: Deps, holder_addr: &Addr) -> StdResult<HolderInfo> {
+    let config: Config = read_config(deps.storage)?;
+    let balance = query_token_balance(
+        deps,
+        &deps.api.addr_humanize(&config.capacorp_contract)?,
+        holder_addr,
+    )?;
+    settle_holder_profit(deps, holder_addr, balance)
+}
+
+/// Holder info settled up to the current profit index, for a holder of balance capacorp
+/// since its last settlement
+pub fn settle_holder_profit(
+    deps: Deps,
+    holder_addr: &Addr,
+    balance: Uint256,
+) -> StdResult<HolderInfo> {
+    let profit_index = read_profit_index(deps.storage)?;
+    let mut holder = read_holder_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(holder_addr.as_str())?,
+    );
+    holder.pending_profit += balance * (profit_index - holder.index);
+    holder.index = profit_index;
+    Ok(holder)
+}
+
+pub fn query_pending_profit(deps: Deps, address: String) -> StdResult<Uint256> {
+    let holder = compute_holder_profit(deps, &deps.api.addr_validate(&address)?)?;
+    Ok(holder.pending_profit)
+}
+
+pub fn query_claimed_profit(deps: Deps, address: String) -> StdResult<Uint256> {
+    let holder_addr = deps.api.addr_validate(&address)?;
+    let holder = read_holder_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(holder_addr.as_str())?,
+    );
+    Ok(holder.claimed_profit)
+}
+
+pub fn query_distribution_schedule(deps: Deps) -> StdResult<DistributionScheduleResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let last_distribution_time = read_last_distribution(deps.storage)?;
+    Ok(DistributionScheduleResponse {
+        keeper_enabled: config.keeper_enabled,
+        last_distribution_time,
+        next_distribution_time: last_distribution_time + config.keeper_interval,
     })
 }
 
+pub fn query_user_orders(deps: Deps, address: String) -> StdResult<UserOrdersResponse> {
+    let owner = deps.api.addr_canonicalize(&address)?;
+    let mut orders = vec![];
+    for id in read_user_order_ids(deps.storage, &owner)? {
+        if let Some(order) = read_order(deps.storage, id)? {
+            orders.push(OrderResponse {
+                id,
+                kind: order.kind,
+                interval: order.interval,
+                next_execution: order.next_execution,
+            });
+        }
+    }
+    Ok(UserOrdersResponse {
+        escrow: read_escrow(deps.storage, &owner),
+        orders,
+    })
+}
+
+const DEFAULT_HISTORY_LIMIT: u32 = 10;
+const MAX_HISTORY_LIMIT: u32 = 30;
+
+pub fn query_account_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AccountHistoryResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .min(MAX_HISTORY_LIMIT) as usize;
+    let account_addr = deps.api.addr_canonicalize(&address)?;
+    let entries = read_history(deps.storage, &account_addr, start_after, limit)?
+        .into_iter()
+        .map(|(id, entry)| HistoryEntryResponse {
+            id,
+            op: entry.op,
+            time: entry.time,
+            ust_amount: entry.ust_amount,
+            cust_amount: entry.cust_amount,
+            exchange_rate: entry.exchange_rate,
+        })
+        .collect();
+    Ok(AccountHistoryResponse { entries })
+}
+
+const DEFAULT_RATE_HISTORY_LIMIT: u32 = 10;
+const MAX_RATE_HISTORY_LIMIT: u32 = 30;
+const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+pub fn query_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RateHistoryResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_RATE_HISTORY_LIMIT)
+        .min(MAX_RATE_HISTORY_LIMIT) as usize;
+    let start_after = start_after.map(|time| time / RATE_SNAPSHOT_PERIOD);
+    let snapshots = read_rate_snapshots(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|snapshot| RateSnapshotResponse {
+            time: snapshot.time,
+            aterra_rate: snapshot.aterra_rate,
+            capa_rate: snapshot.capa_rate,
+        })
+        .collect();
+    Ok(RateHistoryResponse { snapshots })
+}
+
+/// Compares the latest snapshot with the latest one taken at least window_seconds
+/// earlier, or the oldest one if the history is shorter than the window
+pub fn query_realized_apy(
+    deps: Deps,
+    block_time: u64,
+    window_seconds: u64,
+) -> StdResult<RealizedApyResponse> {
+    let end = read_last_rate_snapshot(deps.storage, None)?
+        .ok_or_else(|| StdError::generic_err("No rate snapshot recorded"))?;
+    let start_period = block_time.saturating_sub(window_seconds) / RATE_SNAPSHOT_PERIOD;
+    let start = match read_last_rate_snapshot(deps.storage, Some(start_period + 1))? {
+        Some(snapshot) => snapshot,
+        None => read_rate_snapshots(deps.storage, None, 1)?.remove(0),
+    };
+    if start.time >= end.time {
+        return Err(StdError::generic_err(
+            "Not enough rate snapshots in the window",
+        ));
+    }
+
+    let annualize = Decimal256::from_ratio(SECONDS_PER_YEAR, end.time - start.time);
+    let apy = |start_rate: Decimal256, end_rate: Decimal256| {
+        if end_rate > start_rate {
+            (end_rate / start_rate - Decimal256::one()) * annualize
+        } else {
+            Decimal256::zero()
+        }
+    };
+    Ok(RealizedApyResponse {
+        start_time: start.time,
+        end_time: end.time,
+        aterra_apy: apy(start.aterra_rate, end.aterra_rate),
+        capa_apy: apy(start.capa_rate, end.capa_rate),
+    })
+}
+
+const SECONDS_PER_DAY: u64 = 86400;
+const MAX_PROJECTION_DAYS: u64 = 3650;
+
+/// aUST and cUST exchange rates expected in days, moving the current aUST rate along
+/// the a_terra_exchange_rate curve and following the capa_yield schedule
+pub fn project_exchange_rates(
+    deps: Deps,
+    block_time: u64,
+    days: u64,
+) -> StdResult<(Decimal256, Decimal256)> {
+    if days > MAX_PROJECTION_DAYS {
+        return Err(StdError::generic_err(format!(
+            "Projection limited to {} days",
+            MAX_PROJECTION_DAYS
+        )));
+    }
+    let exchange_rate = query_exchange_rate(deps)?;
+    let day = ExchangeRate::invert_a_terra_exchange_rate(exchange_rate)?;
+    let projected_rate =
+        ExchangeRate::a_terra_exchange_rate(day + Decimal256::from_ratio(days, 1))?;
+    let capa_yield = query_capa_yield(deps, block_time + days * SECONDS_PER_DAY)?;
+    Ok((
+        projected_rate,
+        ExchangeRate::capapult_exchange_rate(projected_rate, capa_yield)?,
+    ))
+}
+
+fn project_cust(
+    deps: Deps,
+    block_time: u64,
+    cust_amount: Uint256,
+    current_value: Uint256,
+    days: u64,
+) -> StdResult<ProjectionResponse> {
+    let (_, projected_exchange_rate) = project_exchange_rates(deps, block_time, days)?;
+    let projected_value = cust_amount * projected_exchange_rate;
+    let projected_interest = if projected_value > current_value {
+        projected_value - current_value
+    } else {
+        Uint256::zero()
+    };
+    Ok(ProjectionResponse {
+        cust_amount,
+        current_value,
+        projected_value,
+        projected_interest,
+        projected_exchange_rate,
+    })
+}
+
+pub fn query_project_balance(
+    deps: Deps,
+    block_time: u64,
+    address: String,
+    days: u64,
+) -> StdResult<ProjectionResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let cust_balance = query_token_balance(
+        deps,
+        &deps.api.addr_humanize(&config.cterra_contract)?,
+        &deps.api.addr_validate(&address)?,
+    )?;
+    let current_value = cust_balance * query_capapult_exchange_rate(deps, block_time)?;
+    project_cust(deps, block_time, cust_balance, current_value, days)
+}
+
+pub fn query_project_deposit(
+    deps: Deps,
+    block_time: u64,
+    amount: Uint256,
+    days: u64,
+) -> StdResult<ProjectionResponse> {
+    let cust_amount = amount / query_capapult_exchange_rate(deps, block_time)?;
+    project_cust(deps, block_time, cust_amount, amount, days)
+}
+
+pub fn query_implied_apy(deps: Deps, block_time: u64) -> StdResult<ImpliedApyResponse> {
+    let exchange_rate = query_exchange_rate(deps)?;
+    let capa_exchange_rate = query_capapult_exchange_rate(deps, block_time)?;
+    let (projected_rate, projected_capa_rate) = project_exchange_rates(deps, block_time, 365)?;
+    let growth = |rate: Decimal256, projected: Decimal256| {
+        if projected > rate {
+            projected / rate - Decimal256::one()
+        } else {
+            Decimal256::zero()
+        }
+    };
+    Ok(ImpliedApyResponse {
+        aterra_apy: growth(exchange_rate, projected_rate),
+        capa_apy: growth(capa_exchange_rate, projected_capa_rate),
+    })
+}
+
+pub fn query_account_summary(
+    deps: Deps,
+    block_time: u64,
+    address: String,
+) -> StdResult<AccountSummaryResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let account_addr = deps.api.addr_validate(&address)?;
+    let account_addr_canon = deps.api.addr_canonicalize(&address)?;
+
+    let exchange_rate = query_exchange_rate(deps)?;
+    let capa_yield = query_capa_yield(deps, block_time)?;
+    let capa_exchange_rate = ExchangeRate::capapult_exchange_rate(exchange_rate, capa_yield)?;
+
+    let cust_balance = query_token_balance(
+        deps,
+        &deps.api.addr_humanize(&config.cterra_contract)?,
+        &account_addr,
+    )?;
+    let ust_value = cust_balance * capa_exchange_rate;
+    let last_ops_ust = read_last_ops_ust(deps.storage, &account_addr_canon, Uint256::zero());
+    let available_harvest = if ust_value > last_ops_ust {
+        ust_value - last_ops_ust
+    } else {
+        Uint256::zero()
+    };
+
+    Ok(AccountSummaryResponse {
+        cust_balance,
+        ust_value,
+        total_deposit: read_total_deposit(deps.storage, &account_addr_canon),
+        last_ops_ust,
+        available_harvest,
+        harvested_sum: read_total_claim(deps.storage, &account_addr_canon),
+        exchange_rate,
+        capa_exchange_rate,
+    })
+}
+
+pub fn query_pending_owner(deps: Deps) -> StdResult<Option<PendingOwnerResponse>> {
+    let pending_owner = match read_pending_owner(deps.storage)? {
+        Some(p) => Some(PendingOwnerResponse {
+            owner_addr: deps.api.addr_humanize(&p.owner_addr)?.to_string(),
+            expires: p.expires,
+        }),
+        None => None,
+    };
+    Ok(pending_owner)
+}
+
 pub fn query_token_supply(deps: Deps, contract_addr: Addr) -> StdResult<Uint256> {
     let token_info: TokenInfoResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
@@ -84,34 +468,35 @@ pub fn query_token_supply(deps: Deps, contract_addr: Addr) -> StdResult<Uint256>
     Ok(Uint256::from(token_info.total_supply))
 }
 
-pub fn query_capapult_rate(deps: Deps) -> StdResult<Decimal256> {
-    let config: Config = read_config(deps.storage)?;
+pub fn query_capapult_rate(deps: Deps, block_time: u64) -> StdResult<Decimal256> {
+    let capa_yield = query_capa_yield(deps, block_time)?;
     let exchange_rate: Decimal256 = query_exchange_rate(deps)?;
-    let capa_exchange_rate =
-        ExchangeRate::capapult_exchange_rate(exchange_rate, config.capa_yield)?;
+    let capa_exchange_rate = ExchangeRate::capapult_exchange_rate(exchange_rate, capa_yield)?;
     Ok(capa_exchange_rate)
 }
 
-pub fn query_dashboard(deps: Deps) -> StdResult<DashboardResponse> {
+pub fn query_dashboard(deps: Deps, block_time: u64) -> StdResult<DashboardResponse> {
     let config: Config = read_config(deps.storage)?;
 
     let cust_total_supply =
         query_token_supply(deps, deps.api.addr_humanize(&config.cterra_contract)?)?;
 
-    let all_accounts: AllAccountsResponse =
+    let token_stats: TokenStatsResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: deps.api.addr_humanize(&config.cterra_contract)?.to_string(),
-            msg: to_binary(&Account::AllAccounts {})?,
+            msg: to_binary(&TokenStatsMsg::TokenStats {})?,
         }))?;
 
     let current_profit = calculate_profit(
         deps,
+        block_time,
         &deps.api.addr_humanize(&config.contract_addr)?,
         &deps.api.addr_humanize(&config.aterra_contract)?,
         cust_total_supply,
     )?;
 
     let total_profit: Uint256 = read_profit(deps.storage)?;
+    let total_insurance: Uint256 = read_insurance(deps.storage)?;
 
     let mut total_value_locked: Uint256 = query_token_balance(
         deps,
@@ -127,7 +512,7 @@ pub fn query_dashboard(deps: Deps) -> StdResult<DashboardResponse> {
 
     total_value_locked = total_value_locked * market_state.prev_exchange_rate;
 
-    let cust_nb_accounts = Uint256::from(all_accounts.accounts.len() as u128);
+    let cust_nb_accounts = Uint256::from(token_stats.holder_count);
 
     let mut cust_avg_balance = Uint256::zero();
     if cust_nb_accounts > Uint256::zero() {
@@ -141,10 +526,25 @@ pub fn query_dashboard(deps: Deps) -> StdResult<DashboardResponse> {
         cust_avg_balance,
         current_profit,
         total_profit,
+        total_insurance,
     })
 }
 
-pub fn query_capacorp_all_accounts(deps: Deps) -> StdResult<Vec<String>> {
+/// Accounting hook registered on a token running the cUST code
+pub fn query_accounting_hook(deps: Deps, token: &Addr) -> StdResult<Option<String>> {
+    let res: AccountingHookResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: token.to_string(),
+            msg: to_binary(&AccountingHookQueryMsg::AccountingHook {})?,
+        }))?;
+    Ok(res.contract)
+}
+
+pub fn query_capacorp_all_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
     let config: Config = read_config(deps.storage)?;
     let all_accounts: AllAccountsResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
@@ -152,7 +552,7 @@ pub fn query_capacorp_all_accounts(deps: Deps) -> StdResult<Vec<String>> {
                 .api
                 .addr_humanize(&config.capacorp_contract)?
                 .to_string(),
-            msg: to_binary(&Account::AllAccounts {})?,
+            msg: to_binary(&Account::AllAccounts { start_after, limit })?,
         }))?;
 
     Ok(all_accounts.accounts)
@@ -160,17 +560,23 @@ pub fn query_capacorp_all_accounts(deps: Deps) -> StdResult<Vec<String>> {
 
 pub fn calculate_profit(
     deps: Deps,
+    block_time: u64,
     earn_contract: &Addr,
     aterra_contract: &Addr,
     total_c_ust_supply: Uint256,
 ) -> StdResult<Uint256> {
     // Load anchor token exchange rate with updated state
     let exchange_rate: Decimal256 = query_exchange_rate(deps)?;
-    let config: Config = read_config(deps.storage)?;
-    let capa_exchange_rate =
-        ExchangeRate::capapult_exchange_rate(exchange_rate, config.capa_yield)?;
+    let capa_yield = query_capa_yield(deps, block_time)?;
+    let capa_exchange_rate = ExchangeRate::capapult_exchange_rate(exchange_rate, capa_yield)?;
 
+    // unclaimed profit and dust belong to capacorp holders
     let total_aterra_amount = query_token_balance(deps, aterra_contract, earn_contract)?;
+    let reserved_profit = read_reserved_profit(deps.storage)?;
+    if total_aterra_amount <= reserved_profit {
+        return Ok(Uint256::zero());
+    }
+    let total_aterra_amount = total_aterra_amount - reserved_profit;
 
     let res1 = total_aterra_amount * exchange_rate;
     let remaining_supply = total_c_ust_supply;
@@ -185,6 +591,7 @@ pub fn calculate_profit(
 
 pub fn calculate_aterra_profit(
     deps: Deps,
+    block_time: u64,
     earn_contract: &Addr,
     aterra_contract: &Addr,
     total_c_ust_supply: Uint256,
@@ -193,6 +600,7 @@ pub fn calculate_aterra_profit(
     let exchange_rate: Decimal256 = query_exchange_rate(deps)?;
     Ok(calculate_profit(
         deps,
+        block_time,
         earn_contract,
         aterra_contract,
         total_c_ust_supply,
@@ -201,18 +609,18 @@ pub fn calculate_aterra_profit(
 
 pub fn query_harvest_value(
     deps: Deps,
+    block_time: u64,
     cust_balance: Uint256,
     account_addr: String,
 ) -> StdResult<Uint256> {
     if cust_balance == Uint256::zero() {
         return Ok(Uint256::from(0u128));
     }
-    let config: Config = read_config(deps.storage)?;
+    let capa_yield = query_capa_yield(deps, block_time)?;
 
     let exchange_rate: Decimal256 = query_exchange_rate(deps)?;
     let account_addr_canon: CanonicalAddr = deps.api.addr_canonicalize(account_addr.as_str())?;
-    let capa_exchange_rate =
-        ExchangeRate::capapult_exchange_rate(exchange_rate, config.capa_yield)?;
+    let capa_exchange_rate = ExchangeRate::capapult_exchange_rate(exchange_rate, capa_yield)?;
     let last_ops_ust = read_last_ops_ust(deps.storage, &account_addr_canon, Uint256::zero());
 
     let current_ust = cust_balance * capa_exchange_rate;
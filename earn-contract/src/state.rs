@@ -1,13 +1,32 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Uint256;
+use crate::msg::{AccountOp, OrderKind};
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{ CanonicalAddr,  StdResult, Storage};
+use cosmwasm_std::Order as IterOrder;
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cw20::Expiration;
 
 pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_PENDING_OWNER: &[u8] = b"pending_owner";
 pub const KEY_BALANCE: &[u8] = b"balance";
 const PREFIX_PROFIT: &[u8] = b"profit";
+const PREFIX_INSURANCE: &[u8] = b"insurance";
+const KEY_YIELD_SCHEDULE: &[u8] = b"yield_schedule";
+const KEY_PROFIT_INDEX: &[u8] = b"profit_index";
+const KEY_UNCLAIMED_PROFIT: &[u8] = b"unclaimed_profit";
+const KEY_PROFIT_DUST: &[u8] = b"profit_dust";
+const KEY_LAST_DISTRIBUTION: &[u8] = b"last_distribution";
+const PREFIX_HOLDER: &[u8] = b"holder";
+const KEY_ORDER_ID: &[u8] = b"order_id";
+const KEY_ORDER_CURSOR: &[u8] = b"order_cursor";
+const PREFIX_ORDER: &[u8] = b"order";
+const PREFIX_USER_ORDER: &[u8] = b"user_order";
+const PREFIX_ESCROW: &[u8] = b"escrow";
+const PREFIX_HISTORY: &[u8] = b"history";
+const PREFIX_HISTORY_SEQ: &[u8] = b"history_seq";
+const PREFIX_RATE_SNAPSHOT: &[u8] = b"rate_snapshot";
 const PREFIX_TOTAL_DEPOSIT: &[u8] = b"td_";
 const PREFIX_LAST_WITHDRAW: &[u8] = b"lw_";
 const PREFIX_TOTAL_CLAIM: &[u8] = b"tc_";
@@ -16,6 +35,7 @@ const PREFIX_TOTAL_CLAIM: &[u8] = b"tc_";
 pub struct Config {
     pub contract_addr: CanonicalAddr,
     pub owner_addr: CanonicalAddr,
+    pub guardian_addr: CanonicalAddr,
     pub market_contract: CanonicalAddr,
     pub aterra_contract: CanonicalAddr,
     pub cterra_contract: CanonicalAddr,
@@ -23,7 +43,17 @@ pub struct Config {
     pub capa_contract: CanonicalAddr,
     pub insurance_contract: CanonicalAddr,
     pub stable_denom: String,
-    pub capa_yield: String,
+    pub capa_yield: Decimal256,
+    pub insurance_share: Decimal256,
+    pub min_deposit: Uint256,
+    pub min_withdraw: Uint256,
+    pub min_distribute_profit: Uint256,
+    pub deposit_paused: bool,
+    pub redeem_paused: bool,
+    pub distribute_paused: bool,
+    pub keeper_enabled: bool,
+    pub keeper_interval: u64,
+    pub keeper_bounty: Decimal256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -34,6 +64,339 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner_addr: CanonicalAddr,
+    pub expires: Expiration,
+}
+
+pub fn store_pending_owner(storage: &mut dyn Storage, data: &PendingOwner) -> StdResult<()> {
+    Singleton::new(storage, KEY_PENDING_OWNER).save(data)
+}
+
+pub fn read_pending_owner(storage: &dyn Storage) -> StdResult<Option<PendingOwner>> {
+    ReadonlySingleton::new(storage, KEY_PENDING_OWNER).may_load()
+}
+
+pub fn remove_pending_owner(storage: &mut dyn Storage) {
+    Singleton::<PendingOwner>::new(storage, KEY_PENDING_OWNER).remove()
+}
+
+/// Linear transition of capa_yield over [start_time, end_time], in seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct YieldSchedule {
+    pub start_yield: Decimal256,
+    pub target_yield: Decimal256,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+impl YieldSchedule {
+    pub fn yield_at(&self, time: u64) -> Decimal256 {
+        if time >= self.end_time {
+            return self.target_yield;
+        }
+        if time <= self.start_time {
+            return self.start_yield;
+        }
+
+        let progress =
+            Decimal256::from_ratio(time - self.start_time, self.end_time - self.start_time);
+        if self.target_yield > self.start_yield {
+            self.start_yield + (self.target_yield - self.start_yield) * progress
+        } else {
+            self.start_yield - (self.start_yield - self.target_yield) * progress
+        }
+    }
+}
+
+pub fn store_yield_schedule(storage: &mut dyn Storage, data: &YieldSchedule) -> StdResult<()> {
+    Singleton::new(storage, KEY_YIELD_SCHEDULE).save(data)
+}
+
+pub fn read_yield_schedule(storage: &dyn Storage) -> StdResult<Option<YieldSchedule>> {
+    ReadonlySingleton::new(storage, KEY_YIELD_SCHEDULE).may_load()
+}
+
+/// aUST profit accrued by a capacorp holder, settled against the global profit index
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HolderInfo {
+    pub index: Decimal256,
+    pub pending_profit: Uint256,
+    pub claimed_profit: Uint256,
+}
+
+/// Accumulated aUST profit per capacorp token
+pub fn store_profit_index(storage: &mut dyn Storage, index: &Decimal256) -> StdResult<()> {
+    Singleton::new(storage, KEY_PROFIT_INDEX).save(index)
+}
+
+pub fn read_profit_index(storage: &dyn Storage) -> StdResult<Decimal256> {
+    Ok(ReadonlySingleton::new(storage, KEY_PROFIT_INDEX)
+        .may_load()?
+        .unwrap_or_else(Decimal256::zero))
+}
+
+/// aUST distributed to capacorp holders and not claimed yet
+pub fn store_unclaimed_profit(storage: &mut dyn Storage, amount: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_UNCLAIMED_PROFIT).save(amount)
+}
+
+pub fn read_unclaimed_profit(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_UNCLAIMED_PROFIT)
+        .may_load()?
+        .unwrap_or_else(Uint256::zero))
+}
+
+/// Profit left over by the rounding of the profit index, carried to the next distribution
+pub fn store_profit_dust(storage: &mut dyn Storage, amount: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_PROFIT_DUST).save(amount)
+}
+
+pub fn read_profit_dust(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_PROFIT_DUST)
+        .may_load()?
+        .unwrap_or_else(Uint256::zero))
+}
+
+/// aUST held for capacorp holders, not backing cUST
+pub fn read_reserved_profit(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(read_unclaimed_profit(storage)? + read_profit_dust(storage)?)
+}
+
+/// Block time of the last distribution, in seconds
+pub fn store_last_distribution(storage: &mut dyn Storage, time: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_LAST_DISTRIBUTION).save(&time)
+}
+
+pub fn read_last_distribution(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(ReadonlySingleton::new(storage, KEY_LAST_DISTRIBUTION)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_holder_info(
+    storage: &mut dyn Storage,
+    holder_addr: &CanonicalAddr,
+    info: &HolderInfo,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_HOLDER).save(holder_addr.as_slice(), info)
+}
+
+pub fn read_holder_info(storage: &dyn Storage, holder_addr: &CanonicalAddr) -> HolderInfo {
+    match ReadonlyBucket::new(storage, PREFIX_HOLDER).load(holder_addr.as_slice()) {
+        Ok(v) => v,
+        _ => HolderInfo {
+            index: Decimal256::zero(),
+            pending_profit: Uint256::zero(),
+            claimed_profit: Uint256::zero(),
+        },
+    }
+}
+
+/// Standing order of a user, run by anyone through ExecuteOrders once due
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Order {
+    pub owner: CanonicalAddr,
+    pub kind: OrderKind,
+    pub interval: u64,
+    pub next_execution: u64,
+}
+
+/// Returns a fresh order id
+pub fn next_order_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = ReadonlySingleton::<u64>::new(storage, KEY_ORDER_ID)
+        .may_load()?
+        .unwrap_or_default()
+        + 1;
+    Singleton::new(storage, KEY_ORDER_ID).save(&id)?;
+    Ok(id)
+}
+
+pub fn store_order(storage: &mut dyn Storage, id: u64, order: &Order) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_ORDER).save(&id.to_be_bytes(), order)?;
+    Bucket::multilevel(storage, &[PREFIX_USER_ORDER, order.owner.as_slice()])
+        .save(&id.to_be_bytes(), &true)
+}
+
+pub fn read_order(storage: &dyn Storage, id: u64) -> StdResult<Option<Order>> {
+    ReadonlyBucket::new(storage, PREFIX_ORDER).may_load(&id.to_be_bytes())
+}
+
+pub fn remove_order(storage: &mut dyn Storage, id: u64, owner: &CanonicalAddr) {
+    Bucket::<Order>::new(storage, PREFIX_ORDER).remove(&id.to_be_bytes());
+    Bucket::<bool>::multilevel(storage, &[PREFIX_USER_ORDER, owner.as_slice()])
+        .remove(&id.to_be_bytes());
+}
+
+/// Order ids of owner, in creation order
+pub fn read_user_order_ids(storage: &dyn Storage, owner: &CanonicalAddr) -> StdResult<Vec<u64>> {
+    ReadonlyBucket::<bool>::multilevel(storage, &[PREFIX_USER_ORDER, owner.as_slice()])
+        .range(None, None, IterOrder::Ascending)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(u64_from_key(&k))
+        })
+        .collect()
+}
+
+/// Up to limit orders with an id greater than start_after
+pub fn read_orders(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<(u64, Order)>> {
+    let start = start_after.map(|id| (id + 1).to_be_bytes());
+    ReadonlyBucket::new(storage, PREFIX_ORDER)
+        .range(start.as_ref().map(|s| &s[..]), None, IterOrder::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((u64_from_key(&k), v))
+        })
+        .collect()
+}
+
+fn u64_from_key(key: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(key);
+    u64::from_be_bytes(bytes)
+}
+
+/// Id of the last order looked at by ExecuteOrders
+pub fn store_order_cursor(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_ORDER_CURSOR).save(&id)
+}
+
+pub fn read_order_cursor(storage: &dyn Storage) -> StdResult<Option<u64>> {
+    ReadonlySingleton::new(storage, KEY_ORDER_CURSOR).may_load()
+}
+
+/// UST pre-paid by a user to fund recurring deposits
+pub fn store_escrow(
+    storage: &mut dyn Storage,
+    account_addr: &CanonicalAddr,
+    amount: &Uint256,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_ESCROW).save(account_addr.as_slice(), amount)
+}
+
+pub fn read_escrow(storage: &dyn Storage, account_addr: &CanonicalAddr) -> Uint256 {
+    ReadonlyBucket::new(storage, PREFIX_ESCROW)
+        .load(account_addr.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+/// Operation of an account, kept in its bounded history
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryEntry {
+    pub op: AccountOp,
+    pub time: u64,
+    pub ust_amount: Uint256,
+    pub cust_amount: Uint256,
+    pub exchange_rate: Decimal256,
+}
+
+/// Appends entry to the history of account_addr, dropping the entries older
+/// than the max_entries most recent ones
+pub fn append_history(
+    storage: &mut dyn Storage,
+    account_addr: &CanonicalAddr,
+    entry: &HistoryEntry,
+    max_entries: u64,
+) -> StdResult<u64> {
+    let id = ReadonlyBucket::<u64>::new(storage, PREFIX_HISTORY_SEQ)
+        .may_load(account_addr.as_slice())?
+        .unwrap_or_default()
+        + 1;
+    Bucket::new(storage, PREFIX_HISTORY_SEQ).save(account_addr.as_slice(), &id)?;
+
+    let mut history = Bucket::multilevel(storage, &[PREFIX_HISTORY, account_addr.as_slice()]);
+    history.save(&id.to_be_bytes(), entry)?;
+    if id > max_entries {
+        history.remove(&(id - max_entries).to_be_bytes());
+    }
+    Ok(id)
+}
+
+/// Up to limit history entries of account_addr with an id greater than start_after
+pub fn read_history(
+    storage: &dyn Storage,
+    account_addr: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<(u64, HistoryEntry)>> {
+    let start = start_after.map(|id| (id + 1).to_be_bytes());
+    ReadonlyBucket::multilevel(storage, &[PREFIX_HISTORY, account_addr.as_slice()])
+        .range(start.as_ref().map(|s| &s[..]), None, IterOrder::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((u64_from_key(&k), v))
+        })
+        .collect()
+}
+
+pub fn remove_history(storage: &mut dyn Storage, account_addr: &CanonicalAddr) -> StdResult<()> {
+    let ids: Vec<u64> = read_history(storage, account_addr, None, usize::MAX)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    let mut history =
+        Bucket::<HistoryEntry>::multilevel(storage, &[PREFIX_HISTORY, account_addr.as_slice()]);
+    for id in ids {
+        history.remove(&id.to_be_bytes());
+    }
+    Ok(())
+}
+
+/// aUST and cUST exchange rates seen at time, at most one per snapshot period
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSnapshot {
+    pub time: u64,
+    pub aterra_rate: Decimal256,
+    pub capa_rate: Decimal256,
+}
+
+pub fn store_rate_snapshot(
+    storage: &mut dyn Storage,
+    period: u64,
+    snapshot: &RateSnapshot,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_RATE_SNAPSHOT).save(&period.to_be_bytes(), snapshot)
+}
+
+pub fn read_rate_snapshot(storage: &dyn Storage, period: u64) -> StdResult<Option<RateSnapshot>> {
+    ReadonlyBucket::new(storage, PREFIX_RATE_SNAPSHOT).may_load(&period.to_be_bytes())
+}
+
+/// Up to limit snapshots of the periods following start_after, oldest first
+pub fn read_rate_snapshots(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<RateSnapshot>> {
+    let start = start_after.map(|period| (period + 1).to_be_bytes());
+    ReadonlyBucket::new(storage, PREFIX_RATE_SNAPSHOT)
+        .range(start.as_ref().map(|s| &s[..]), None, IterOrder::Ascending)
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+/// Latest snapshot of a period before end (default: any period)
+pub fn read_last_rate_snapshot(
+    storage: &dyn Storage,
+    end: Option<u64>,
+) -> StdResult<Option<RateSnapshot>> {
+    let end = end.map(|period| period.to_be_bytes());
+    ReadonlyBucket::new(storage, PREFIX_RATE_SNAPSHOT)
+        .range(None, end.as_ref().map(|e| &e[..]), IterOrder::Descending)
+        .next()
+        .map(|item| Ok(item?.1))
+        .transpose()
+}
+
 pub fn store_profit(storage: &mut dyn Storage, profit: &Uint256) -> StdResult<()> {
     Singleton::new(storage, PREFIX_PROFIT).save(profit)
 }
@@ -42,6 +405,16 @@ pub fn read_profit(storage: &dyn Storage) -> StdResult<Uint256> {
     ReadonlySingleton::new(storage, PREFIX_PROFIT).load()
 }
 
+pub fn store_insurance(storage: &mut dyn Storage, insurance: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, PREFIX_INSURANCE).save(insurance)
+}
+
+pub fn read_insurance(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, PREFIX_INSURANCE)
+        .may_load()?
+        .unwrap_or_else(Uint256::zero))
+}
+
 pub fn store_total_deposit(
     storage: &mut dyn Storage,
     account_addr: &CanonicalAddr,
@@ -1,9 +1,18 @@
-use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::Config;
+use crate::contract::{
+    execute, instantiate, query, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT,
+    MAX_YIELD_RAMP_DURATION,
+};
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, PendingOwnerResponse, QueryMsg,
+    YieldScheduleResponse,
+};
+use crate::state::{read_config, Config};
 use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{ mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR,};
-use cosmwasm_std::{ from_binary, Api, Coin,  OwnedDeps, StdResult, Uint128,};
+use cosmwasm_std::{ attr, from_binary, Api, Coin,  OwnedDeps, StdResult, Uint128,};
+use cw20::Expiration;
 
 fn get_register_contracts(
     deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
@@ -47,6 +56,7 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
     Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        guardian_addr: deps.api.addr_canonicalize("guardian").unwrap(),
         aterra_contract: deps.api.addr_canonicalize("aterra_contract").unwrap(),
         market_contract: deps.api.addr_canonicalize("market").unwrap(),
         cterra_contract: deps.api.addr_canonicalize("cterra_contract").unwrap(),
@@ -54,7 +64,17 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
         capa_contract: deps.api.addr_canonicalize("capa_contract").unwrap(),
         insurance_contract: deps.api.addr_canonicalize("insurance_contract").unwrap(),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
+        insurance_share: Decimal256::zero(),
+        min_deposit: Uint256::from(1_000_000u128),
+        min_withdraw: Uint256::from(1_000_000u128),
+        min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        deposit_paused: false,
+        redeem_paused: false,
+        distribute_paused: false,
+        keeper_enabled: false,
+        keeper_interval: DEFAULT_KEEPER_INTERVAL,
+        keeper_bounty: Decimal256::zero(),
     }
 }
 
@@ -77,7 +97,7 @@ fn test_update_config() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -102,14 +122,30 @@ fn test_update_config() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let new_owner = deps.api.addr_validate("owner2").unwrap();
+    let new_guardian = deps.api.addr_validate("guardian2").unwrap();
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some(new_owner),
+        guardian_addr: Some(new_guardian),
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
     };
 
     let info = mock_info("owner", &[]);
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
 
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner2", &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwnership {}).unwrap();
+
     let msg = QueryMsg::Config {};
 
     let res = &query(deps.as_ref(), mock_env(), msg.clone());
@@ -167,6 +203,7 @@ fn test_update_config() {
                 bin.unwrap(),
                 ConfigResponse {
                     owner_addr: owner_addr,
+                    guardian_addr: "guardian2".to_string(),
                     market_contract: market_contract,
                     aterra_contract: aterra_contract,
                     cterra_contract: cterra_contract,
@@ -174,7 +211,17 @@ fn test_update_config() {
                     capa_contract: capa_contract,
                     insurance_contract: insurance_contract,
                     stable_denom: "uusd".to_string(),
-                    capa_yield: "100".to_string(),
+                    capa_yield: Decimal256::one(),
+                    insurance_share: Decimal256::zero(),
+                    min_deposit: Uint256::from(1_000_000u128),
+                    min_withdraw: Uint256::from(1_000_000u128),
+                    min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+                    deposit_paused: false,
+                    redeem_paused: false,
+                    distribute_paused: false,
+                    keeper_enabled: false,
+                    keeper_interval: DEFAULT_KEEPER_INTERVAL,
+                    keeper_bounty: Decimal256::zero(),
                 }
             );
         }
@@ -182,3 +229,430 @@ fn test_update_config() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+#[test]
+fn register_contracts_twice() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::ContractsAlreadyRegistered {}, msg),
+    }
+}
+
+#[test]
+fn two_step_ownership_transfer() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // nothing to accept yet
+    let info = mock_info("owner2", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwnership {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::NoPendingOwner {}, msg),
+    }
+
+    let expires = Expiration::AtHeight(mock_env().block.height + 10);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: Some(expires),
+    };
+
+    // only the owner can propose
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: Option<PendingOwnerResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        pending,
+        Some(PendingOwnerResponse {
+            owner_addr: String::from("owner2"),
+            expires,
+        })
+    );
+
+    // only the proposed owner can accept
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwnership {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    // the proposal cannot be accepted once expired
+    let mut env = mock_env();
+    env.block.height += 10;
+    let info = mock_info("owner2", &[]);
+    let res = execute(deps.as_mut(), env, info.clone(), ExecuteMsg::AcceptOwnership {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::OwnershipProposalExpired {}, msg),
+    }
+
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwnership {}).unwrap();
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.owner_addr, deps.api.addr_canonicalize("owner2").unwrap());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: Option<PendingOwnerResponse> = from_binary(&res).unwrap();
+    assert_eq!(pending, None);
+}
+
+#[test]
+fn cancel_ownership_proposal() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: None,
+    };
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+
+    let info = mock_info("owner2", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwnership {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::NoPendingOwner {}, msg),
+    }
+}
+
+#[test]
+fn invalid_capa_yield() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::zero(),
+    };
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InvalidCapaYield {
+                capa_yield: Decimal256::zero()
+            },
+            msg
+        ),
+    }
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::from_ratio(625, 1000),
+    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.capa_yield, Decimal256::from_ratio(625, 1000));
+
+    let msg = ExecuteMsg::UpdateYield {
+        target: Decimal256::percent(101),
+        duration: 0,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InvalidCapaYield {
+                capa_yield: Decimal256::percent(101)
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateYield {
+        target: Decimal256::one(),
+        duration: 0,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.capa_yield, Decimal256::one());
+}
+
+#[test]
+fn update_yield_ramp() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::percent(50),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateYield {
+        target: Decimal256::one(),
+        duration: 1000,
+    };
+
+    // only the owner can update the yield
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    // the ramp length is capped, which also keeps end_time from overflowing
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateYield {
+            target: Decimal256::one(),
+            duration: u64::MAX,
+        },
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::YieldRampTooLong {
+                max: MAX_YIELD_RAMP_DURATION
+            },
+            msg
+        ),
+    }
+
+    let start_time = mock_env().block.time.seconds();
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // halfway through the ramp
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(500);
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::YieldSchedule {}).unwrap();
+    let schedule: YieldScheduleResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        schedule,
+        YieldScheduleResponse {
+            current_yield: Decimal256::percent(75),
+            start_yield: Decimal256::percent(50),
+            target_yield: Decimal256::one(),
+            start_time,
+            end_time: start_time + 1000,
+        }
+    );
+
+    // market exchange rate is 1 in the mock
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::ExchangeRate {}).unwrap();
+    let rate: Decimal256 = from_binary(&res).unwrap();
+    assert_eq!(rate, Decimal256::percent(75));
+
+    // a new update starts from the effective yield
+    let msg = ExecuteMsg::UpdateYield {
+        target: Decimal256::percent(50),
+        duration: 500,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::YieldSchedule {}).unwrap();
+    let schedule: YieldScheduleResponse = from_binary(&res).unwrap();
+    assert_eq!(schedule.current_yield, Decimal256::percent(75));
+    assert_eq!(schedule.start_yield, Decimal256::percent(75));
+
+    env.block.time = env.block.time.plus_seconds(500);
+    let res = query(deps.as_ref(), env, QueryMsg::YieldSchedule {}).unwrap();
+    let schedule: YieldScheduleResponse = from_binary(&res).unwrap();
+    assert_eq!(schedule.current_yield, Decimal256::percent(50));
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.capa_yield, Decimal256::percent(50));
+}
+
+#[test]
+fn update_contracts() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateContracts {
+        market_contract: Some("market2".to_string()),
+        aterra_contract: None,
+        cterra_contract: Some("cterra2".to_string()),
+        capacorp_contract: None,
+        capa_contract: None,
+        insurance_contract: Some("insurance2".to_string()),
+    };
+
+    // nothing to update before registration
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::ContractsNotRegistered {}, msg),
+    }
+
+    let register_msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info, register_msg).unwrap();
+
+    // only the owner can update contracts
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_contracts"),
+            attr("market_contract", "market2"),
+            attr("cterra_contract", "cterra2"),
+            attr("insurance_contract", "insurance2"),
+        ]
+    );
+
+    let config = read_config(&deps.storage).unwrap();
+    assert_eq!(
+        config.market_contract,
+        deps.api.addr_canonicalize("market2").unwrap()
+    );
+    assert_eq!(
+        config.cterra_contract,
+        deps.api.addr_canonicalize("cterra2").unwrap()
+    );
+    assert_eq!(
+        config.insurance_contract,
+        deps.api.addr_canonicalize("insurance2").unwrap()
+    );
+    assert_eq!(config.aterra_contract, mock_config.aterra_contract);
+    assert_eq!(config.capacorp_contract, mock_config.capacorp_contract);
+    assert_eq!(config.capa_contract, mock_config.capa_contract);
+}
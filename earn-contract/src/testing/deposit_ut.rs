@@ -1,21 +1,34 @@
-use crate::contract::{execute, instantiate, INITIAL_DEPOSIT_AMOUNT};
-use crate::deposit::redeem_stable;
-use crate::msg::{ExecuteMsg, InstantiateMsg};
+use crate::contract::{
+    execute, instantiate, query, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT,
+};
+use crate::deposit::{redeem_stable, MAX_HISTORY_ENTRIES};
+use crate::error::ContractError;
+use crate::msg::{
+    AccountHistoryResponse, AccountOp, ExecuteMsg, HistoryEntryResponse, InstantiateMsg, QueryMsg,
+    RedeemStableHookMsg,
+};
 use crate::querier::query_token_balance;
-use crate::state::Config;
+use crate::state::{
+    read_history, read_last_ops_ust, read_total_claim, read_total_deposit, store_last_ops_ust,
+    store_total_deposit, Config,
+};
 use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{
     mock_env, mock_info, MockApi,  MockStorage, MOCK_CONTRACT_ADDR,
 };
 use cosmwasm_std::{
-    attr, Api, Binary, Coin,  OwnedDeps, StdError,  Uint128,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg,  OwnedDeps,  Uint128,
+    WasmMsg,
 };
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use std::str;
 
 fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> Config {
     Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        guardian_addr: deps.api.addr_canonicalize("guardian").unwrap(),
         aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
         market_contract: deps.api.addr_canonicalize("market").unwrap(),
         cterra_contract: deps.api.addr_canonicalize("cterra_contract").unwrap(),
@@ -23,7 +36,17 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
         capa_contract: deps.api.addr_canonicalize("capa_contract").unwrap(),
         insurance_contract: deps.api.addr_canonicalize("insurance_contract").unwrap(),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
+        insurance_share: Decimal256::zero(),
+        min_deposit: Uint256::from(1_000_000u128),
+        min_withdraw: Uint256::from(1_000_000u128),
+        min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        deposit_paused: false,
+        redeem_paused: false,
+        distribute_paused: false,
+        keeper_enabled: false,
+        keeper_interval: DEFAULT_KEEPER_INTERVAL,
+        keeper_bounty: Decimal256::zero(),
     }
 }
 
@@ -45,7 +68,7 @@ fn instantiate_with_wrong_initial_amount() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -60,8 +83,11 @@ fn instantiate_with_wrong_initial_amount() {
     match res {
         Ok(msg) => panic!("DO NOT ENTER HERE"),
         Err(msg) => assert_eq!(
-            "Generic error: Must deposit initial funds 100000000\"uusd\"",
-            msg.to_string()
+            ContractError::InvalidInitialDeposit {
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+                denom: "uusd".to_string(),
+            },
+            msg
         ),
     }
 }
@@ -84,7 +110,7 @@ fn register_contract_with_wrong_owner() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -110,7 +136,7 @@ fn register_contract_with_wrong_owner() {
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
     match res {
         Ok(msg) => panic!("DO NOT ENTER HERE"),
-        Err(msg) => assert_eq!("Generic error: Unauthorized", msg.to_string()),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
     }
 }
 
@@ -132,7 +158,7 @@ fn too_small_deposit() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -164,7 +190,7 @@ fn too_small_deposit() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -175,11 +201,61 @@ fn too_small_deposit() {
 
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "Deposit amount must be greater than 1 UST uusd")
+        Err(ContractError::DepositTooSmall { min, denom }) => {
+            assert_eq!(min, Uint256::from(1_000_000u128));
+            assert_eq!(denom, "uusd")
         }
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    // thresholds cannot be zero
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: Some(Uint256::zero()),
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::InvalidMinimum {}, msg),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: Some(Uint256::from(10_000_000u128)),
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit { recipient: None };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(5_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Ok(msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::DepositTooSmall {
+                min: Uint256::from(10_000_000u128),
+                denom: "uusd".to_string()
+            },
+            msg
+        ),
+    }
 }
 
 #[test]
@@ -201,7 +277,7 @@ fn proper_deposit() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -255,7 +331,7 @@ fn proper_deposit() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -264,6 +340,7 @@ fn proper_deposit() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "addr0000"),
+                    attr("recipient", "addr0000"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -293,7 +370,7 @@ fn withdraw_too_much() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -347,7 +424,7 @@ fn withdraw_too_much() {
             amount: Uint128::from(55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -356,6 +433,7 @@ fn withdraw_too_much() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "addr0000"),
+                    attr("recipient", "addr0000"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -381,12 +459,18 @@ fn withdraw_too_much() {
         mock_env(),
         sender,
         Uint128::from(55_555_555_000_000u128),
+        None,
+        None,
     );
     match res {
         Ok(_msg) => panic!("DO NOT ENTER HERE"),
-        Err(msg) =>  assert_eq!(
-            "Generic error: Not enough aust available; redeem amount 55555555000000 larger than current balance 55555554750000", 
-            msg.to_string()),
+        Err(msg) => assert_eq!(
+            ContractError::InsufficientAust {
+                requested: Uint256::from(55555555000000u128),
+                available: Uint256::from(55555554750000u128),
+            },
+            msg
+        ),
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
@@ -410,7 +494,7 @@ fn withdraw_too_little() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -465,7 +549,7 @@ fn withdraw_too_little() {
             amount: Uint128::from(55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -474,6 +558,7 @@ fn withdraw_too_little() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "addr0000"),
+                    attr("recipient", "addr0000"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -501,15 +586,56 @@ fn withdraw_too_little() {
     )]);
 
     let sender = deps.api.addr_validate(&"addr0000").unwrap();
-    let res = redeem_stable(deps.as_mut(), mock_env(), sender, Uint128::zero());
+    let res = redeem_stable(
+        deps.as_mut(),
+        mock_env(),
+        sender.clone(),
+        Uint128::zero(),
+        None,
+        None,
+    );
     match res {
         Ok(msg) => panic!("DO NOT ENTER HERE"),
         Err(msg) => assert_eq!(
-            "Generic error: Withdrawal amount must be greater than 1 UST uusd",
-            msg.to_string()
+            ContractError::WithdrawTooSmall {
+                min: Uint256::from(1_000_000u128),
+                denom: "uusd".to_string()
+            },
+            msg
         ),
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: Some(Uint256::from(100_000_000u128)),
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = redeem_stable(
+        deps.as_mut(),
+        mock_env(),
+        sender,
+        Uint128::from(50_000_000u128),
+        None,
+        None,
+    );
+    match res {
+        Ok(msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::WithdrawTooSmall {
+                min: Uint256::from(100_000_000u128),
+                denom: "uusd".to_string()
+            },
+            msg
+        ),
+    }
 }
 
 #[test]
@@ -531,7 +657,7 @@ fn proper_withdraw() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -584,7 +710,7 @@ fn proper_withdraw() {
             amount: Uint128::from(55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -593,6 +719,7 @@ fn proper_withdraw() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "addr0000"),
+                    attr("recipient", "addr0000"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -616,6 +743,8 @@ fn proper_withdraw() {
         mock_env(),
         sender,
         Uint128::from(55555554750000u128),
+        None,
+        None,
     );
     match res {
         Ok(msg) => {
@@ -623,6 +752,7 @@ fn proper_withdraw() {
                 msg.attributes,
                 vec![
                     attr("action", "redeem_stable"),
+                    attr("recipient", "addr0000"),
                     attr("burn_amount cust", "55555554750000"),
                     attr("aust_burn_amount aust", "55555554750000"),
                     attr("withdraw_amount ust", "55555554750000"),
@@ -633,3 +763,576 @@ fn proper_withdraw() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+#[test]
+fn deposit_for_recipient() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let info = mock_info(
+        "router",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(55_555_555_000_000u128),
+        }],
+    );
+    let msg = ExecuteMsg::Deposit {
+        recipient: Some("addr0000".to_string()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("cterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from("addr0000"),
+                amount: Uint128::from(55_555_555_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_stable"),
+            attr("depositor", "router"),
+            attr("recipient", "addr0000"),
+            attr("mint_amount", "55555555000000"),
+            attr("deposit_amount", "55555555000000"),
+        ]
+    );
+
+    // accounting is booked to the recipient
+    let recipient = deps.api.addr_canonicalize("addr0000").unwrap();
+    let router = deps.api.addr_canonicalize("router").unwrap();
+    assert_eq!(
+        read_total_deposit(&deps.storage, &recipient),
+        Uint256::from(55_555_555_000_000u128)
+    );
+    assert_eq!(read_total_deposit(&deps.storage, &router), Uint256::zero());
+}
+
+#[test]
+fn redeem_to_recipient_with_min_receive() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"aterra_contract".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_555_555_000_000u128),
+        )],
+    )]);
+
+    // proceeds below the caller's minimum revert the redemption
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(100_000_000u128),
+        msg: to_binary(&RedeemStableHookMsg::RedeemStable {
+            recipient: Some(String::from("zap")),
+            min_receive: Some(Uint128::from(100_000_001u128)),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("cterra_contract", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::RedeemBelowMinimum {
+                amount: Uint256::from(100_000_000u128),
+                min_receive: Uint128::from(100_000_001u128),
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(100_000_000u128),
+        msg: to_binary(&RedeemStableHookMsg::RedeemStable {
+            recipient: Some(String::from("zap")),
+            min_receive: Some(Uint128::from(100_000_000u128)),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("zap"),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100_000_000u128),
+            }],
+        })
+    );
+    assert_eq!(res.attributes[1], attr("recipient", "zap"));
+}
+
+#[test]
+fn harvest_interest() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(55_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000_000u128))],
+        ),
+    ]);
+
+    // 900 UST of principal, 100 UST of accrued interest
+    let account = deps.api.addr_canonicalize("addr0000").unwrap();
+    store_last_ops_ust(&mut deps.storage, &account, &Uint256::from(900_000_000u128)).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Harvest {
+        amount: Some(Uint256::from(100_000_001u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::HarvestTooLarge {
+                requested: Uint256::from(100_000_001u128),
+                available: Uint256::from(100_000_000u128),
+            },
+            msg
+        ),
+    }
+
+    // the cUST is burnt from the harvester's balance
+    let msg = ExecuteMsg::Harvest { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InsufficientAllowance {
+                requested: Uint256::from(100_000_000u128),
+                available: Uint256::zero(),
+            },
+            msg
+        ),
+    }
+    deps.querier.with_allowance(
+        "cterra_contract",
+        "addr0000",
+        MOCK_CONTRACT_ADDR,
+        Uint128::from(99_999_999u128),
+    );
+    let msg = ExecuteMsg::Harvest { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InsufficientAllowance {
+                requested: Uint256::from(100_000_000u128),
+                available: Uint256::from(99_999_999u128),
+            },
+            msg
+        ),
+    }
+
+    deps.querier.with_allowance(
+        "cterra_contract",
+        "addr0000",
+        MOCK_CONTRACT_ADDR,
+        Uint128::from(100_000_000u128),
+    );
+    let msg = ExecuteMsg::Harvest { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("cterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::BurnFrom {
+                owner: String::from("addr0000"),
+                amount: Uint128::from(100_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100_000_000u128),
+            }],
+        })
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "harvest"),
+            attr("harvester", "addr0000"),
+            attr("burn_amount", "100000000"),
+            attr("harvest_amount", "100000000"),
+            attr("withdraw_amount", "100000000"),
+        ]
+    );
+
+    // principal is left deposited
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &account, Uint256::zero()),
+        Uint256::from(900_000_000u128)
+    );
+    assert_eq!(
+        read_total_claim(&deps.storage, &account),
+        Uint256::from(100_000_000u128)
+    );
+}
+
+#[test]
+fn account_history() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"aterra_contract".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_555_555_000_000u128),
+        )],
+    )]);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(200_000_000u128),
+        }],
+    );
+    let msg = ExecuteMsg::Deposit { recipient: None };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(60);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(50_000_000u128),
+        msg: to_binary(&RedeemStableHookMsg::RedeemStable {
+            recipient: None,
+            min_receive: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("cterra_contract", &[]), msg).unwrap();
+
+    let msg = QueryMsg::AccountHistory {
+        address: String::from("addr0000"),
+        start_after: None,
+        limit: None,
+    };
+    let res: AccountHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.entries,
+        vec![
+            HistoryEntryResponse {
+                id: 1,
+                op: AccountOp::Deposit,
+                time: mock_env().block.time.seconds(),
+                ust_amount: Uint256::from(200_000_000u128),
+                cust_amount: Uint256::from(200_000_000u128),
+                exchange_rate: Decimal256::one(),
+            },
+            HistoryEntryResponse {
+                id: 2,
+                op: AccountOp::Redeem,
+                time: env.block.time.seconds(),
+                ust_amount: Uint256::from(50_000_000u128),
+                cust_amount: Uint256::from(50_000_000u128),
+                exchange_rate: Decimal256::one(),
+            },
+        ]
+    );
+
+    let msg = QueryMsg::AccountHistory {
+        address: String::from("addr0000"),
+        start_after: Some(1),
+        limit: Some(1),
+    };
+    let res: AccountHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.entries.len(), 1);
+    assert_eq!(res.entries[0].id, 2);
+
+    // only the latest MAX_HISTORY_ENTRIES operations are kept
+    let account = deps.api.addr_canonicalize("addr0000").unwrap();
+    for _ in 0..MAX_HISTORY_ENTRIES {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(2_000_000u128),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit { recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+    let history = read_history(&deps.storage, &account, None, usize::MAX).unwrap();
+    assert_eq!(history.len() as u64, MAX_HISTORY_ENTRIES);
+    assert_eq!(history[0].0, 3);
+
+    let msg = ExecuteMsg::RemoveAccount {
+        addr: Some(Addr::unchecked("addr0000")),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        read_history(&deps.storage, &account, None, usize::MAX).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn balance_moved_moves_basis() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // addr0000 deposited 800 UST and now holds 1000 cUST, 250 of which it moved to addr0001
+    let sender = deps.api.addr_canonicalize("addr0000").unwrap();
+    let recipient = deps.api.addr_canonicalize("addr0001").unwrap();
+    store_last_ops_ust(&mut deps.storage, &sender, &Uint256::from(800_000_000u128)).unwrap();
+    store_total_deposit(&mut deps.storage, &sender, &Uint256::from(800_000_000u128)).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"cterra_contract".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(750_000_000u128)),
+            (&"addr0001".to_string(), &Uint128::from(250_000_000u128)),
+        ],
+    )]);
+
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: String::from("addr0000"),
+        recipient: String::from("addr0001"),
+        amount: Uint128::from(250_000_000u128),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cterra_contract", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("moved_ops_ust", "200000000"));
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &sender, Uint256::zero()),
+        Uint256::from(600_000_000u128)
+    );
+    assert_eq!(
+        read_total_deposit(&deps.storage, &sender),
+        Uint256::from(600_000_000u128)
+    );
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &recipient, Uint256::zero()),
+        Uint256::from(200_000_000u128)
+    );
+    assert_eq!(
+        read_total_deposit(&deps.storage, &recipient),
+        Uint256::from(200_000_000u128)
+    );
+
+    // moving the whole balance moves the whole basis
+    deps.querier.with_token_balances(&[(
+        &"cterra_contract".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1_000_000_000u128))],
+    )]);
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: String::from("addr0001"),
+        recipient: String::from("addr0000"),
+        amount: Uint128::from(250_000_000u128),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cterra_contract", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &sender, Uint256::zero()),
+        Uint256::from(800_000_000u128)
+    );
+    assert_eq!(
+        read_total_deposit(&deps.storage, &recipient),
+        Uint256::zero()
+    );
+
+    // cUST sent to this contract keeps the basis of its sender
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: String::from("addr0000"),
+        recipient: MOCK_CONTRACT_ADDR.to_string(),
+        amount: Uint128::from(250_000_000u128),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cterra_contract", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes.len(), 4);
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &sender, Uint256::zero()),
+        Uint256::from(800_000_000u128)
+    );
+}
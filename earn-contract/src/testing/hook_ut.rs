@@ -0,0 +1,228 @@
+use crate::contract::{execute, instantiate, INITIAL_DEPOSIT_AMOUNT};
+use crate::msg::{AccountingHookQueryMsg, AccountingHookResponse, ExecuteMsg, InstantiateMsg};
+use crate::state::{
+    read_last_ops_ust, read_total_deposit, store_last_ops_ust, store_total_deposit,
+};
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{
+    mock_dependencies as mock_token_dependencies, mock_env, mock_info, MockApi, MockQuerier,
+    MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Api, Coin, CosmosMsg, OwnedDeps, Response, Uint128, WasmMsg,
+};
+use cw20::Cw20Coin;
+use cw20_base::contract::{
+    execute as token_execute, instantiate as token_instantiate, query as token_query,
+    query_balance as token_balance,
+};
+use cw20_base::msg::{ExecuteMsg as TokenExecuteMsg, InstantiateMsg as TokenInstantiateMsg};
+use cw20_base::ContractError as TokenError;
+
+type EarnDeps = OwnedDeps<MockStorage, MockApi, WasmMockQuerier>;
+type TokenDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+const ACCOUNTS: [&str; 2] = ["addr0000", "addr0001"];
+
+fn setup() -> EarnDeps {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+// instantiates the cUST token code, owned by token_owner
+fn setup_token(token_owner: &str, symbol: &str) -> TokenDeps {
+    let mut deps = mock_token_dependencies(&[]);
+    let msg = TokenInstantiateMsg {
+        name: symbol.to_string(),
+        symbol: symbol.to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: String::from("addr0000"),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+        mint: None,
+        marketing: None,
+    };
+    token_instantiate(deps.as_mut(), mock_env(), mock_info(token_owner, &[]), msg).unwrap();
+    deps
+}
+
+// shows the token balances to the earn contract
+fn sync_balances(deps: &mut EarnDeps, cust: &TokenDeps, capacorp: &TokenDeps) {
+    let balances = |token: &TokenDeps| -> Vec<(String, Uint128)> {
+        ACCOUNTS
+            .iter()
+            .map(|addr| {
+                let balance = token_balance(token.as_ref(), addr.to_string()).unwrap();
+                (addr.to_string(), balance.balance)
+            })
+            .collect()
+    };
+    let cust_balances = balances(cust);
+    let capacorp_balances = balances(capacorp);
+    let cust_balances: Vec<(&String, &Uint128)> =
+        cust_balances.iter().map(|(a, b)| (a, b)).collect();
+    let capacorp_balances: Vec<(&String, &Uint128)> =
+        capacorp_balances.iter().map(|(a, b)| (a, b)).collect();
+    deps.querier.with_token_balances(&[
+        (&"cterra_contract".to_string(), &cust_balances),
+        (&"capacorp_contract".to_string(), &capacorp_balances),
+    ]);
+}
+
+// runs the messages token sent to the earn contract, as token
+fn forward(deps: &mut EarnDeps, token: &str, res: Response) -> Vec<Response> {
+    res.messages
+        .into_iter()
+        .filter_map(|sub_msg| match sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr == MOCK_CONTRACT_ADDR => Some(msg),
+            _ => None,
+        })
+        .map(|msg| {
+            let msg: ExecuteMsg = from_binary(&msg).unwrap();
+            execute(deps.as_mut(), mock_env(), mock_info(token, &[]), msg).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn token_owner_registers_earn_hook() {
+    let mut token = setup_token("token_owner", "cUST");
+
+    // the earn contract cannot register itself, only the token owner can
+    let msg = TokenExecuteMsg::UpdateAccountingHook {
+        contract: Some(MOCK_CONTRACT_ADDR.to_string()),
+    };
+    for sender in [MOCK_CONTRACT_ADDR, "owner"].iter() {
+        let res = token_execute(
+            token.as_mut(),
+            mock_env(),
+            mock_info(sender, &[]),
+            msg.clone(),
+        );
+        match res {
+            Ok(_msg) => panic!("DO NOT ENTER HERE"),
+            Err(msg) => assert_eq!(TokenError::Unauthorized {}, msg),
+        }
+    }
+    token_execute(
+        token.as_mut(),
+        mock_env(),
+        mock_info("token_owner", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // the earn contract reads the hook back with its own messages
+    let res = token_query(
+        token.as_ref(),
+        mock_env(),
+        from_binary(&to_binary(&AccountingHookQueryMsg::AccountingHook {}).unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<AccountingHookResponse>(&res).unwrap(),
+        AccountingHookResponse {
+            contract: Some(MOCK_CONTRACT_ADDR.to_string()),
+        }
+    );
+}
+
+#[test]
+fn token_transfers_reach_earn() {
+    let mut deps = setup();
+    let mut cust = setup_token("token_owner", "cUST");
+    let mut capacorp = setup_token("token_owner", "CAPACORP");
+    for token in [&mut cust, &mut capacorp].iter_mut() {
+        let msg = TokenExecuteMsg::UpdateAccountingHook {
+            contract: Some(MOCK_CONTRACT_ADDR.to_string()),
+        };
+        token_execute(
+            token.as_mut(),
+            mock_env(),
+            mock_info("token_owner", &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    // addr0000 deposited 800 UST for its 1000 cUST
+    let sender = deps.api.addr_canonicalize("addr0000").unwrap();
+    let recipient = deps.api.addr_canonicalize("addr0001").unwrap();
+    store_last_ops_ust(&mut deps.storage, &sender, &Uint256::from(800_000_000u128)).unwrap();
+    store_total_deposit(&mut deps.storage, &sender, &Uint256::from(800_000_000u128)).unwrap();
+
+    // a cUST transfer moves the basis of the sent share
+    let msg = TokenExecuteMsg::Transfer {
+        recipient: String::from("addr0001"),
+        amount: Uint128::from(250_000_000u128),
+    };
+    let res = token_execute(cust.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    sync_balances(&mut deps, &cust, &capacorp);
+    let res = forward(&mut deps, "cterra_contract", res);
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].attributes[0], attr("action", "balance_moved"));
+    assert_eq!(
+        read_last_ops_ust(&deps.storage, &sender, Uint256::zero()),
+        Uint256::from(600_000_000u128)
+    );
+    assert_eq!(
+        read_total_deposit(&deps.storage, &recipient),
+        Uint256::from(200_000_000u128)
+    );
+
+    // a capacorp transfer settles both holders
+    let msg = TokenExecuteMsg::Transfer {
+        recipient: String::from("addr0001"),
+        amount: Uint128::from(100_000_000u128),
+    };
+    let res = token_execute(
+        capacorp.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg,
+    )
+    .unwrap();
+    sync_balances(&mut deps, &cust, &capacorp);
+    let res = forward(&mut deps, "capacorp_contract", res);
+    assert_eq!(res.len(), 1);
+    assert_eq!(
+        res[0].attributes,
+        vec![
+            attr("action", "capacorp_moved"),
+            attr("sender", "addr0000"),
+            attr("recipient", "addr0001"),
+            attr("amount", "100000000"),
+        ]
+    );
+}
@@ -231,4 +231,21 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_capapult_exchange_rate() {
+        assert_eq!(
+            Decimal256::from_ratio(125, 100),
+            ExchangeRate::capapult_exchange_rate(
+                Decimal256::from_ratio(2, 1),
+                Decimal256::from_ratio(625, 1000)
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Decimal256::from_ratio(12, 10),
+            ExchangeRate::capapult_exchange_rate(Decimal256::from_ratio(12, 10), Decimal256::one())
+                .unwrap()
+        );
+    }
 }
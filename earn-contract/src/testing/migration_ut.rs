@@ -0,0 +1,122 @@
+use crate::contract::{instantiate, migrate, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT};
+use crate::error::ContractError;
+use crate::migrations::generate_v04_test_config;
+use crate::msg::{InstantiateMsg, MigrateMsg};
+use crate::state::read_config;
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{Coin, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+
+#[test]
+fn migrate_v04_config() {
+    let mut deps = mock_dependencies(&[]);
+    generate_v04_test_config(deps.as_mut().storage, "55");
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.capa_yield, Decimal256::percent(55));
+    assert_eq!(config.guardian_addr, config.owner_addr);
+    assert_eq!(config.insurance_share, Decimal256::zero());
+    assert_eq!(config.min_deposit, Uint256::from(1_000_000u128));
+    assert_eq!(config.min_withdraw, Uint256::from(1_000_000u128));
+    assert_eq!(
+        config.min_distribute_profit,
+        Uint256::from(INITIAL_DEPOSIT_AMOUNT)
+    );
+    assert!(!config.deposit_paused);
+    assert!(!config.keeper_enabled);
+    assert_eq!(config.keeper_interval, DEFAULT_KEEPER_INTERVAL);
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // migrating an up to date config is a no-op
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(read_config(deps.as_ref().storage).unwrap(), config);
+}
+
+#[test]
+fn migrate_v04_config_unparsable_yield() {
+    // v0.4.x silently used 55% for values it could not parse
+    let mut deps = mock_dependencies(&[]);
+    generate_v04_test_config(deps.as_mut().storage, "0.55");
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InvalidLegacyCapaYield {
+                capa_yield: "0.55".to_string()
+            },
+            msg
+        ),
+    }
+
+    // the legacy config is left in place
+    assert!(read_config(deps.as_ref().storage).is_err());
+}
+
+#[test]
+fn migrate_v04_config_invalid_yield() {
+    let mut deps = mock_dependencies(&[]);
+    generate_v04_test_config(deps.as_mut().storage, "120");
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InvalidCapaYield {
+                capa_yield: Decimal256::percent(120)
+            },
+            msg
+        ),
+    }
+}
+
+#[test]
+fn migrate_checks_contract_version() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::percent(55),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let config = read_config(deps.as_ref().storage).unwrap();
+
+    // nothing to migrate for an instance of the current version
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(read_config(deps.as_ref().storage).unwrap(), config);
+
+    for (contract, version) in [
+        ("crates.io:earn-contract", "0.3.0"),
+        ("crates.io:cw20-base", env!("CARGO_PKG_VERSION")),
+    ]
+    .iter()
+    {
+        set_contract_version(deps.as_mut().storage, *contract, *version).unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+        match res {
+            Ok(_msg) => panic!("DO NOT ENTER HERE"),
+            Err(msg) => assert_eq!(
+                ContractError::UnsupportedMigration {
+                    contract: contract.to_string(),
+                    version: version.to_string(),
+                },
+                msg
+            ),
+        }
+    }
+}
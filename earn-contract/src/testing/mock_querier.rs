@@ -2,7 +2,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::str;
 
-use crate::msg::{ConfigResponse, MarketStateResponse};
+use crate::contract::DEFAULT_KEEPER_INTERVAL;
+use crate::msg::{AccountingHookResponse, ConfigResponse, MarketStateResponse, TokenStatsResponse};
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
@@ -10,7 +11,9 @@ use cosmwasm_std::{
     QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
-use cw20::{AllAccountsResponse, BalanceResponse, TokenInfoResponse};
+use cw20::{
+    AllAccountsResponse, AllowanceResponse, BalanceResponse, Expiration, TokenInfoResponse,
+};
 use std::collections::HashMap;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -27,7 +30,16 @@ pub enum QueryMsg {
     Balance {
         address: String,
     },
-    AllAccounts {},
+    Allowance {
+        owner: String,
+        spender: String,
+    },
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    TokenStats {},
+    AccountingHook {},
 }
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -48,6 +60,10 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
+    // tokens not listed report this contract as their accounting hook
+    accounting_hooks: HashMap<String, Option<String>>,
+    // (token, owner, spender) => allowance, zero if not listed
+    allowances: HashMap<(String, String, String), Uint128>,
 }
 
 #[derive(Clone, Default)]
@@ -149,11 +165,19 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 let matcher: QueryMsg = from_binary(&msg).unwrap();
                 match matcher {
-                    QueryMsg::AllAccounts {} => {
+                    QueryMsg::AllAccounts { start_after, limit } => {
                         let mut vec = Vec::new();
                         vec.push("daniel".to_string());
                         vec.push("bruno".to_string());
 
+                        if let Some(start_after) = start_after {
+                            let pos = vec.iter().position(|a| *a == start_after).unwrap();
+                            vec = vec.split_off(pos + 1);
+                        }
+                        if let Some(limit) = limit {
+                            vec.truncate(limit as usize);
+                        }
+
                         SystemResult::Ok(ContractResult::from(to_binary(&AllAccountsResponse {
                             accounts: vec,
                         })))
@@ -161,6 +185,7 @@ impl WasmMockQuerier {
                     QueryMsg::Config {} => {
                         SystemResult::Ok(ContractResult::from(to_binary(&ConfigResponse {
                             owner_addr: String::from(""),
+                            guardian_addr: String::from(""),
                             aterra_contract: String::from(""),
                             market_contract: String::from(""),
                             cterra_contract: String::from(""),
@@ -168,7 +193,17 @@ impl WasmMockQuerier {
                             capacorp_contract: String::from(""),
                             insurance_contract: String::from(""),
                             stable_denom: "uusd".to_string(),
-                            capa_yield: "100".to_string(),
+                            capa_yield: Decimal256::one(),
+                            insurance_share: Decimal256::zero(),
+                            min_deposit: Uint256::zero(),
+                            min_withdraw: Uint256::zero(),
+                            min_distribute_profit: Uint256::zero(),
+                            deposit_paused: false,
+                            redeem_paused: false,
+                            distribute_paused: false,
+                            keeper_enabled: false,
+                            keeper_interval: DEFAULT_KEEPER_INTERVAL,
+                            keeper_bounty: Decimal256::zero(),
                         })))
                     }
                     QueryMsg::State {} => {
@@ -201,6 +236,39 @@ impl WasmMockQuerier {
                             balance: *balance,
                         })))
                     }
+                    QueryMsg::Allowance { owner, spender } => {
+                        let allowance = self
+                            .allowances
+                            .get(&(contract_addr.clone(), owner, spender))
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&AllowanceResponse {
+                            allowance,
+                            expires: Expiration::Never {},
+                        })))
+                    }
+                    QueryMsg::TokenStats {} => {
+                        let balances: HashMap<String, Uint128> =
+                            match self.token_querier.balances.get(contract_addr) {
+                                Some(balances) => balances.clone(),
+                                None => HashMap::new(),
+                            };
+
+                        let mut total_supply = Uint128::zero();
+                        let mut holder_count = 0u64;
+
+                        for balance in balances {
+                            if !balance.1.is_zero() {
+                                total_supply += balance.1;
+                                holder_count += 1;
+                            }
+                        }
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&TokenStatsResponse {
+                            total_supply: Uint256::from(total_supply),
+                            holder_count,
+                        })))
+                    }
                     QueryMsg::TokenInfo {} => {
                         let balances: HashMap<String, Uint128> =
                             match self.token_querier.balances.get(contract_addr) {
@@ -221,6 +289,15 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::AccountingHook {} => {
+                        let contract = match self.accounting_hooks.get(contract_addr) {
+                            Some(hook) => hook.clone(),
+                            None => Some(MOCK_CONTRACT_ADDR.to_string()),
+                        };
+                        SystemResult::Ok(ContractResult::from(to_binary(&AccountingHookResponse {
+                            contract,
+                        })))
+                    }
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -281,6 +358,8 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
+            accounting_hooks: HashMap::new(),
+            allowances: HashMap::new(),
         }
     }
 
@@ -297,4 +376,18 @@ impl WasmMockQuerier {
     pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
         self.token_querier = TokenQuerier::new(balances);
     }
+
+    // configure the accounting hook a token reports
+    pub fn with_accounting_hook(&mut self, token: &str, hook: Option<&str>) {
+        self.accounting_hooks
+            .insert(token.to_string(), hook.map(|h| h.to_string()));
+    }
+
+    // configure the allowance owner granted spender on token
+    pub fn with_allowance(&mut self, token: &str, owner: &str, spender: &str, amount: Uint128) {
+        self.allowances.insert(
+            (token.to_string(), owner.to_string(), spender.to_string()),
+            amount,
+        );
+    }
 }
@@ -1,6 +1,10 @@
 mod config_ut;
 mod deposit_ut;
+mod hook_ut;
 mod math_ut;
+mod migration_ut;
 mod mock_querier;
+mod orders_ut;
+mod pause_ut;
 mod profit_ut;
 mod query_ut;
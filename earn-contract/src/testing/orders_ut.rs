@@ -0,0 +1,428 @@
+use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
+use crate::error::ContractError;
+use crate::msg::{
+    DepositStableHandleMsg, ExecuteMsg, InstantiateMsg, OrderKind, OrderResponse, QueryMsg,
+    UserOrdersResponse,
+};
+use crate::orders::MAX_ORDER_INTERVAL;
+use crate::state::read_total_deposit;
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Api, BankMsg, Coin, CosmosMsg, OwnedDeps, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+fn setup() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+fn query_user_orders(
+    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    address: &str,
+) -> UserOrdersResponse {
+    let msg = QueryMsg::UserOrders {
+        address: address.to_string(),
+    };
+    from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+#[test]
+fn manage_orders() {
+    let mut deps = setup();
+    let info = mock_info("addr0000", &[]);
+
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(10_000_000u128),
+        },
+        interval: 0,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::InvalidOrderInterval {}, msg),
+    }
+
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(10_000_000u128),
+        },
+        interval: u64::MAX,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::OrderIntervalTooLong {
+                max: MAX_ORDER_INTERVAL
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(1_000_000u128),
+        },
+        interval: 3600,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::DepositTooSmall {
+                min: Uint256::from(1_000_000u128),
+                denom: "uusd".to_string(),
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(10_000_000u128),
+        },
+        interval: 3600,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_order"),
+            attr("owner", "addr0000"),
+            attr("order_id", "1"),
+        ]
+    );
+
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(20_000_000u128),
+        },
+        interval: 86400,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // an update is validated like a new order
+    let msg = ExecuteMsg::UpdateOrder {
+        id: 2,
+        kind: None,
+        interval: Some(MAX_ORDER_INTERVAL + 1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::OrderIntervalTooLong {
+                max: MAX_ORDER_INTERVAL
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateOrder {
+        id: 1,
+        kind: None,
+        interval: Some(7200),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::CancelOrder { id: 2 };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::OrderNotFound { id: 2 }, msg),
+    }
+
+    assert_eq!(
+        query_user_orders(&deps, "addr0000"),
+        UserOrdersResponse {
+            escrow: Uint256::zero(),
+            orders: vec![OrderResponse {
+                id: 1,
+                kind: OrderKind::RecurringDeposit {
+                    amount: Uint256::from(10_000_000u128),
+                },
+                interval: 7200,
+                next_execution: mock_env().block.time.seconds(),
+            }],
+        }
+    );
+}
+
+#[test]
+fn execute_recurring_deposit() {
+    let mut deps = setup();
+    deps.querier.with_token_balances(&[(
+        &"aterra_contract".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_555_555_000_000u128),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::FundEscrow {};
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::NoFunds {
+                denom: "uusd".to_string()
+            },
+            msg
+        ),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(25_000_000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::FundEscrow {}).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(10_000_000u128),
+        },
+        interval: 3600,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // anyone can run due orders
+    let msg = ExecuteMsg::ExecuteOrders { limit: None };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("market_contract"),
+            msg: to_binary(&DepositStableHandleMsg::DepositStable {}).unwrap(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(10_000_000u128),
+            }],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("cterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from("addr0000"),
+                amount: Uint128::from(10_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_orders"),
+            attr("executed", "1"),
+            attr("skipped", ""),
+        ]
+    );
+    let account = deps.api.addr_canonicalize("addr0000").unwrap();
+    assert_eq!(
+        read_total_deposit(&deps.storage, &account),
+        Uint256::from(10_000_000u128)
+    );
+
+    // not due again before the interval elapsed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // 5 UST left in escrow, the order is skipped and stays due
+    env.block.time = env.block.time.plus_seconds(3600);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(res.attributes[2], attr("skipped", "1"));
+    assert_eq!(
+        res.attributes[3],
+        attr(
+            "order_error",
+            "1: Escrow amount 10000000 larger than escrow balance 5000000"
+        )
+    );
+
+    let orders = query_user_orders(&deps, "addr0000");
+    assert_eq!(orders.escrow, Uint256::from(5_000_000u128));
+    assert_eq!(
+        orders.orders[0].next_execution,
+        mock_env().block.time.seconds() + 7200
+    );
+
+    let msg = ExecuteMsg::WithdrawEscrow {
+        amount: Some(Uint256::from(5_000_001u128)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InsufficientEscrow {
+                requested: Uint256::from(5_000_001u128),
+                available: Uint256::from(5_000_000u128),
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::WithdrawEscrow { amount: None };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(5_000_000u128),
+            }],
+        })
+    );
+    assert_eq!(query_user_orders(&deps, "addr0000").escrow, Uint256::zero());
+}
+
+#[test]
+fn failed_order_does_not_block_the_queue() {
+    let mut deps = setup();
+    deps.querier.with_token_balances(&[(
+        &"aterra_contract".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_555_555_000_000u128),
+        )],
+    )]);
+
+    // addr0000 never funds its escrow, addr0001 does
+    let msg = ExecuteMsg::CreateOrder {
+        kind: OrderKind::RecurringDeposit {
+            amount: Uint256::from(10_000_000u128),
+        },
+        interval: 3600,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10_000_000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::FundEscrow {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::ExecuteOrders { limit: Some(1) };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_orders"),
+            attr("executed", ""),
+            attr("skipped", "1"),
+            attr(
+                "order_error",
+                "1: Escrow amount 10000000 larger than escrow balance 0"
+            ),
+        ]
+    );
+
+    // the next call starts after the failed order
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_orders"),
+            attr("executed", "2"),
+            attr("skipped", ""),
+        ]
+    );
+}
@@ -0,0 +1,156 @@
+use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
+use crate::error::ContractError;
+use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, RedeemStableHookMsg};
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{from_binary, to_binary, Addr, Coin, OwnedDeps, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+fn setup() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        market_contract: String::from("market_contract"),
+        aterra_contract: String::from("aterra_contract"),
+        cterra_contract: String::from("cterra_contract"),
+        capacorp_contract: String::from("capacorp_contract"),
+        capa_contract: String::from("capa_contract"),
+        insurance_contract: String::from("insurance_contract"),
+    };
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: Some(Addr::unchecked("guardian")),
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps
+}
+
+fn query_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> ConfigResponse {
+    from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap()
+}
+
+#[test]
+fn guardian_pause_and_owner_unpause() {
+    let mut deps = setup();
+
+    let msg = ExecuteMsg::Pause {
+        deposit: true,
+        redeem: true,
+        distribute: false,
+    };
+
+    // only guardian or owner can pause
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let info = mock_info("guardian", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config = query_config(&deps);
+    assert!(config.deposit_paused);
+    assert!(config.redeem_paused);
+    assert!(!config.distribute_paused);
+
+    let msg = ExecuteMsg::Unpause {
+        deposit: true,
+        redeem: false,
+        distribute: false,
+    };
+
+    // guardian cannot unpause
+    let info = mock_info("guardian", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config = query_config(&deps);
+    assert!(!config.deposit_paused);
+    assert!(config.redeem_paused);
+    assert!(!config.distribute_paused);
+}
+
+#[test]
+fn paused_operations_are_rejected() {
+    let mut deps = setup();
+
+    let msg = ExecuteMsg::Pause {
+        deposit: true,
+        redeem: true,
+        distribute: true,
+    };
+    let info = mock_info("guardian", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit { recipient: None });
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::DepositPaused {}, msg),
+    }
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::DistributePaused {}, msg),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        msg: to_binary(&RedeemStableHookMsg::RedeemStable {
+            recipient: None,
+            min_receive: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("cterra_contract", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::RedeemPaused {}, msg),
+    }
+}
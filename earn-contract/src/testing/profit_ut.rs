@@ -1,15 +1,23 @@
-use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
-use crate::msg::{ConfigResponse, DashboardResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::Config;
+use crate::contract::{
+    execute, instantiate, query, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT,
+    MAX_KEEPER_INTERVAL,
+};
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, DashboardResponse, DistributionScheduleResponse, ExecuteMsg, InstantiateMsg,
+    QueryMsg,
+};
+use crate::state::{store_profit_dust, Config};
 use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{
     mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
 };
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Api, Coin, Deps, DepsMut, MemoryStorage, OwnedDeps, StdError,
-    StdResult, Uint128,
+    attr, from_binary, to_binary, Api, Coin, CosmosMsg, Deps, DepsMut, MemoryStorage, OwnedDeps,
+    StdResult, Uint128, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 
 fn get_register_contracts(
     deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
@@ -53,6 +61,7 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
     Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        guardian_addr: deps.api.addr_canonicalize("guardian").unwrap(),
         aterra_contract: deps.api.addr_canonicalize("aterra_contract").unwrap(),
         market_contract: deps.api.addr_canonicalize("market").unwrap(),
         cterra_contract: deps.api.addr_canonicalize("cterra_contract").unwrap(),
@@ -60,7 +69,17 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
         capa_contract: deps.api.addr_canonicalize("capa_contract").unwrap(),
         insurance_contract: deps.api.addr_canonicalize("insurance_contract").unwrap(),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
+        insurance_share: Decimal256::zero(),
+        min_deposit: Uint256::from(1_000_000u128),
+        min_withdraw: Uint256::from(1_000_000u128),
+        min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        deposit_paused: false,
+        redeem_paused: false,
+        distribute_paused: false,
+        keeper_enabled: false,
+        keeper_interval: DEFAULT_KEEPER_INTERVAL,
+        keeper_bounty: Decimal256::zero(),
     }
 }
 
@@ -91,7 +110,7 @@ fn not_authorized_distribute() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -138,7 +157,7 @@ fn not_authorized_distribute() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -147,6 +166,7 @@ fn not_authorized_distribute() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "addr0000"),
+                    attr("recipient", "addr0000"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -159,7 +179,7 @@ fn not_authorized_distribute() {
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => panic!("DO NOT ENTER HERE"),
-        Err(msg) => assert_eq!("Generic error: Unauthorized", msg.to_string()),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
@@ -183,7 +203,7 @@ fn too_little_profit_distribute() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -230,7 +250,7 @@ fn too_little_profit_distribute() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
         }],
     );
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -239,6 +259,7 @@ fn too_little_profit_distribute() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "owner"),
+                    attr("recipient", "owner"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -251,8 +272,9 @@ fn too_little_profit_distribute() {
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => panic!("Should be an error here"),
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "Too little profit to distribute: 0")
+        Err(ContractError::ProfitBelowThreshold { profit, min }) => {
+            assert_eq!(profit, Uint256::zero());
+            assert_eq!(min, Uint256::from(INITIAL_DEPOSIT_AMOUNT));
         }
         _ => panic!("DO NOT ENTER HERE"),
     }
@@ -276,7 +298,7 @@ fn proper_distribute() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -324,7 +346,7 @@ fn proper_distribute() {
         }],
     );
 
-    let msg = ExecuteMsg::Deposit {};
+    let msg = ExecuteMsg::Deposit { recipient: None };
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
@@ -333,6 +355,7 @@ fn proper_distribute() {
                 vec![
                     attr("action", "deposit_stable"),
                     attr("depositor", "owner"),
+                    attr("recipient", "owner"),
                     attr("mint_amount", "55555555000000"),
                     attr("deposit_amount", "55555555000000"),
                 ]
@@ -357,18 +380,63 @@ fn proper_distribute() {
                 &Uint128::from(10_555_555_000_000u128),
             )],
         ),
+        (
+            &"capacorp_contract".to_string(),
+            &[(&"daniel".to_string(), &Uint128::from(100_000u128))],
+        ),
     ]);
+    // profit stays below a raised threshold
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: Some(Uint256::from(100_000_000_000_000u128)),
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Distribute {});
+    match res {
+        Ok(msg) => panic!("Should be an error here"),
+        Err(msg) => assert_eq!(
+            ContractError::ProfitBelowThreshold {
+                profit: Uint256::from(90_000_000_000_000u128),
+                min: Uint256::from(100_000_000_000_000u128),
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: Some(Uint256::from(INITIAL_DEPOSIT_AMOUNT)),
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
     let msg = ExecuteMsg::Distribute {};
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
     match res {
         Ok(msg) => {
+            // no insurance transfer for a zero share
+            assert_eq!(msg.messages.len(), 0);
             assert_eq!(
                 msg.attributes,
                 vec![
                     attr("action", "distribute"),
                     attr("insurance", "0"),
-                    attr("daniel", "0"),
-                    attr("bruno", "0"),
+                    attr("profit", "90000000000000"),
+                    attr("dust", "0"),
+                    attr("bounty", "0"),
+                    attr("profit_index", "900000000"),
                 ]
             );
         }
@@ -376,3 +444,1087 @@ fn proper_distribute() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+#[test]
+fn distribute_with_insurance_share() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+    let info = mock_info("addr0000", &[]);
+    //setting up the required infoironment for the function call (inputs)
+
+    let mock_config = get_mock_config(&deps);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Ok(msg) => {
+            assert_eq!(msg.attributes.len(), 0)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // insurance share is capped
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: Some(Decimal256::percent(51)),
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::InsuranceShareTooHigh {
+                max: Decimal256::percent(50)
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: Some(Decimal256::percent(10)),
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: None,
+        keeper_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let info = mock_info(
+        "owner",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(55_555_555_000_000u128),
+        }],
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
+        }],
+    );
+
+    let msg = ExecuteMsg::Deposit { recipient: None };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Ok(msg) => {
+            assert_eq!(
+                msg.attributes,
+                vec![
+                    attr("action", "deposit_stable"),
+                    attr("depositor", "owner"),
+                    attr("recipient", "owner"),
+                    attr("mint_amount", "55555555000000"),
+                    attr("deposit_amount", "55555555000000"),
+                ]
+            );
+        }
+        Err(msg) => panic!("DO NOT ENTER HERE"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[(&"daniel".to_string(), &Uint128::from(100_000u128))],
+        ),
+    ]);
+    let msg = ExecuteMsg::Distribute {};
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Ok(msg) => {
+            assert_eq!(
+                msg.messages[0].msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: String::from("aterra_contract"),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: String::from("insurance_contract"),
+                        amount: Uint128::from(9_000_000_000_000u128),
+                    })
+                    .unwrap(),
+                })
+            );
+            assert_eq!(
+                msg.attributes,
+                vec![
+                    attr("action", "distribute"),
+                    attr("insurance", "9000000000000"),
+                    attr("profit", "81000000000000"),
+                    attr("dust", "0"),
+                    attr("bounty", "0"),
+                    attr("profit_index", "810000000"),
+                ]
+            );
+        }
+        Err(msg) => panic!("{}", msg.to_string()),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Dashboard {}).unwrap();
+    let dashboard: DashboardResponse = from_binary(&res).unwrap();
+    assert_eq!(dashboard.total_profit, Uint256::from(81_000_000_000_000u128));
+    assert_eq!(dashboard.total_insurance, Uint256::from(9_000_000_000_000u128));
+}
+
+#[test]
+fn claim_distributed_profit() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+
+    // nothing distributed yet
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("daniel", &[]),
+        ExecuteMsg::ClaimProfit {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::NothingToClaim {}, msg),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("insurance", "0"),
+            attr("profit", "90000000000000"),
+            attr("dust", "0"),
+            attr("bounty", "0"),
+            attr("profit_index", "900000000"),
+        ]
+    );
+
+    // distributed profit is no longer counted as profit
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::ProfitBelowThreshold {
+                profit: Uint256::zero(),
+                min: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+            msg
+        ),
+    }
+
+    let msg = QueryMsg::PendingProfit {
+        address: "bruno".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let pending: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(pending, Uint256::from(36_000_000_000_000u128));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("daniel", &[]),
+        ExecuteMsg::ClaimProfit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("aterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("daniel"),
+                amount: Uint128::from(54_000_000_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("daniel", &[]),
+        ExecuteMsg::ClaimProfit {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::NothingToClaim {}, msg),
+    }
+
+    let msg = QueryMsg::PendingProfit {
+        address: "daniel".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let pending: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(pending, Uint256::zero());
+
+    let msg = QueryMsg::ClaimedProfit {
+        address: "daniel".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let claimed: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(claimed, Uint256::from(54_000_000_000_000u128));
+}
+
+#[test]
+fn capacorp_transfer_keeps_past_profit() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {}).unwrap();
+
+    // bruno claims, then daniel and bruno move all their capacorp to new addresses
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bruno", &[]),
+        ExecuteMsg::ClaimProfit {},
+    )
+    .unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(64_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"alice".to_string(), &Uint128::from(60_000u128)),
+                (&"carol".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+
+    // only capacorp can report its transfers
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: "daniel".to_string(),
+        recipient: "alice".to_string(),
+        amount: Uint128::from(60_000u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg.clone());
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("capacorp_contract", &[]),
+        msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: "bruno".to_string(),
+        recipient: "carol".to_string(),
+        amount: Uint128::from(40_000u128),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("capacorp_contract", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // capacorp received after the distribution earns nothing from it
+    for holder in ["alice", "carol"].iter() {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(holder, &[]),
+            ExecuteMsg::ClaimProfit {},
+        );
+        match res {
+            Ok(_msg) => panic!("DO NOT ENTER HERE"),
+            Err(msg) => assert_eq!(ContractError::NothingToClaim {}, msg),
+        }
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bruno", &[]),
+        ExecuteMsg::ClaimProfit {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::NothingToClaim {}, msg),
+    }
+
+    // daniel keeps the share earned while holding
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("daniel", &[]),
+        ExecuteMsg::ClaimProfit {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("aterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("daniel"),
+                amount: Uint128::from(54_000_000_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+}
+
+#[test]
+fn capacorp_self_transfer_settles_nothing() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {}).unwrap();
+
+    // daniel sends capacorp to himself twice, his balance never changes
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: "daniel".to_string(),
+        recipient: "daniel".to_string(),
+        amount: Uint128::from(60_000u128),
+    };
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("capacorp_contract", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+    }
+
+    let msg = QueryMsg::PendingProfit {
+        address: "daniel".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let pending: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(pending, Uint256::from(54_000_000_000_000u128));
+}
+
+#[test]
+fn capacorp_moved_to_earn_is_settled() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {}).unwrap();
+
+    // daniel moves all his capacorp to the earn contract
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+    let msg = ExecuteMsg::BalanceMoved {
+        sender: "daniel".to_string(),
+        recipient: MOCK_CONTRACT_ADDR.to_string(),
+        amount: Uint128::from(60_000u128),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("capacorp_contract", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // daniel keeps his share, the earn contract earns nothing from the past distribution
+    let msg = QueryMsg::PendingProfit {
+        address: "daniel".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let pending: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(pending, Uint256::from(54_000_000_000_000u128));
+
+    let msg = QueryMsg::PendingProfit {
+        address: MOCK_CONTRACT_ADDR.to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let pending: Uint256 = from_binary(&res).unwrap();
+    assert_eq!(pending, Uint256::zero());
+}
+
+#[test]
+fn distribute_requires_capacorp_hook() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+
+    // capacorp not reporting its transfers, or reporting them elsewhere
+    for hook in [None, Some("other_contract")].iter() {
+        deps.querier
+            .with_accounting_hook("capacorp_contract", *hook);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Distribute {},
+        );
+        match res {
+            Ok(_msg) => panic!("DO NOT ENTER HERE"),
+            Err(msg) => assert_eq!(ContractError::CapacorpHookNotRegistered {}, msg),
+        }
+    }
+
+    deps.querier
+        .with_accounting_hook("capacorp_contract", Some(MOCK_CONTRACT_ADDR));
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {}).unwrap();
+}
+
+#[test]
+fn distribute_keeps_reserved_profit_backed() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(60_000u128)),
+                (&"bruno".to_string(), &Uint128::from(40_000u128)),
+            ],
+        ),
+    ]);
+
+    // carried dust the aUST balance cannot back
+    store_profit_dust(
+        deps.as_mut().storage,
+        &Uint256::from(200_000_000_000_000u128),
+    )
+    .unwrap();
+
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::ReservedProfitExceedsBalance {
+                reserved: Uint256::from(200_000_000_000_000u128),
+                balance: Uint256::from(100_555_555_000_000u128),
+            },
+            msg
+        ),
+    }
+}
+
+#[test]
+fn distribute_carries_rounding_dust() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // a capacorp supply of 7 cannot split the profit exactly
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[
+                (&"daniel".to_string(), &Uint128::from(3u128)),
+                (&"bruno".to_string(), &Uint128::from(4u128)),
+            ],
+        ),
+    ]);
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("insurance", "0"),
+            attr("profit", "89999999999999"),
+            attr("dust", "1"),
+            attr("bounty", "0"),
+            attr("profit_index", "12857142857142.857142857142857142"),
+        ]
+    );
+
+    // holder shares never add up to more than the distributed profit
+    let mut total_pending = Uint256::zero();
+    for holder in ["daniel", "bruno"].iter() {
+        let msg = QueryMsg::PendingProfit {
+            address: holder.to_string(),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let pending: Uint256 = from_binary(&res).unwrap();
+        total_pending += pending;
+    }
+    assert!(total_pending <= Uint256::from(89_999_999_999_999u128));
+
+    // the dust is carried over, not counted as new profit
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::ProfitBelowThreshold {
+                profit: Uint256::one(),
+                min: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+            msg
+        ),
+    }
+}
+
+#[test]
+fn keeper_distribute() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = get_register_contracts(&deps, &mock_config);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &"aterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(100_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"cterra_contract".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(10_555_555_000_000u128),
+            )],
+        ),
+        (
+            &"capacorp_contract".to_string(),
+            &[(&"daniel".to_string(), &Uint128::from(100_000u128))],
+        ),
+    ]);
+
+    // keeper mode is off by default
+    let keeper = mock_info("keeper", &[]);
+    let res = execute(deps.as_mut(), mock_env(), keeper.clone(), ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::Unauthorized {}, msg),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: Some(true),
+        keeper_interval: None,
+        keeper_bounty: Some(Decimal256::percent(11)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::KeeperBountyTooHigh {
+                max: Decimal256::percent(10)
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: Some(true),
+        keeper_interval: Some(0),
+        keeper_bounty: Some(Decimal256::percent(1)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(ContractError::InvalidKeeperInterval {}, msg),
+    }
+
+    // the next distribution time must stay representable
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: Some(true),
+        keeper_interval: Some(u64::MAX),
+        keeper_bounty: Some(Decimal256::percent(1)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::KeeperIntervalTooLong {
+                max: MAX_KEEPER_INTERVAL
+            },
+            msg
+        ),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        guardian_addr: None,
+        insurance_share: None,
+        min_deposit: None,
+        min_withdraw: None,
+        min_distribute_profit: None,
+        keeper_enabled: Some(true),
+        keeper_interval: None,
+        keeper_bounty: Some(Decimal256::percent(1)),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), keeper.clone(), ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("aterra_contract"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("keeper"),
+                amount: Uint128::from(900_000_000_000u128),
+            })
+            .unwrap(),
+        })
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("insurance", "0"),
+            attr("profit", "89100000000000"),
+            attr("dust", "0"),
+            attr("bounty", "900000000000"),
+            attr("profit_index", "891000000"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::DistributionSchedule {}).unwrap();
+    let schedule: DistributionScheduleResponse = from_binary(&res).unwrap();
+    let last_time = env.block.time.seconds();
+    assert_eq!(
+        schedule,
+        DistributionScheduleResponse {
+            keeper_enabled: true,
+            last_distribution_time: last_time,
+            next_distribution_time: last_time + DEFAULT_KEEPER_INTERVAL,
+        }
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_KEEPER_INTERVAL - 1);
+    let res = execute(deps.as_mut(), env, keeper, ExecuteMsg::Distribute {});
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            ContractError::DistributeTooEarly {
+                next_time: last_time + DEFAULT_KEEPER_INTERVAL
+            },
+            msg
+        ),
+    }
+}
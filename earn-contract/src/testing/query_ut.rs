@@ -1,14 +1,24 @@
-use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
-use crate::msg::{ConfigResponse, DashboardResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::Config;
+use crate::contract::{
+    execute, instantiate, query, DEFAULT_KEEPER_INTERVAL, INITIAL_DEPOSIT_AMOUNT,
+};
+use crate::deposit::RATE_SNAPSHOT_PERIOD;
+use crate::msg::{
+    AccountSummaryResponse, ConfigResponse, DashboardResponse, ExecuteMsg, ImpliedApyResponse,
+    InstantiateMsg, ProjectionResponse, QueryMsg, RateHistoryResponse, RateSnapshotResponse,
+    RealizedApyResponse,
+};
+use crate::state::{
+    store_last_ops_ust, store_rate_snapshot, store_total_claim, store_total_deposit, Config,
+    RateSnapshot,
+};
 use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
-use cosmwasm_bignumber::{ Uint256};
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{
     mock_env, mock_info, MockApi,  MockStorage, MOCK_CONTRACT_ADDR,
 };
 use cosmwasm_std::{
      from_binary,  Api, Coin, OwnedDeps, 
-    StdResult, Uint128,
+    StdError, StdResult, Uint128,
 };
 
 fn get_register_contracts(
@@ -53,6 +63,7 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
     Config {
         contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
         owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        guardian_addr: deps.api.addr_canonicalize("guardian").unwrap(),
         aterra_contract: deps.api.addr_canonicalize("aterra_contract").unwrap(),
         market_contract: deps.api.addr_canonicalize("market").unwrap(),
         cterra_contract: deps.api.addr_canonicalize("cterra_contract").unwrap(),
@@ -60,7 +71,17 @@ fn get_mock_config(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> C
         capa_contract: deps.api.addr_canonicalize("capa_contract").unwrap(),
         insurance_contract: deps.api.addr_canonicalize("insurance_contract").unwrap(),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
+        insurance_share: Decimal256::zero(),
+        min_deposit: Uint256::from(1_000_000u128),
+        min_withdraw: Uint256::from(1_000_000u128),
+        min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        deposit_paused: false,
+        redeem_paused: false,
+        distribute_paused: false,
+        keeper_enabled: false,
+        keeper_interval: DEFAULT_KEEPER_INTERVAL,
+        keeper_bounty: Decimal256::zero(),
     }
 }
 
@@ -83,7 +104,7 @@ fn test_query_dashboard() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -133,7 +154,7 @@ fn test_query_dashboard() {
 
     let total_value_locked: Uint256 = Uint256::zero();
     let cust_total_supply: Uint256 = Uint256::zero();
-    let cust_nb_accounts: Uint256 = Uint256::from(2u128);
+    let cust_nb_accounts: Uint256 = Uint256::zero();
     let cust_avg_balance: Uint256 = Uint256::zero();
     let current_profit: Uint256 = Uint256::zero();
     let total_profit: Uint256 = Uint256::zero();
@@ -149,6 +170,7 @@ fn test_query_dashboard() {
                     cust_avg_balance,
                     current_profit,
                     total_profit,
+                    total_insurance: Uint256::zero(),
                 }
             );
         }
@@ -166,10 +188,16 @@ fn test_query_dashboard() {
         ),
         (
             &"cterra_contract".to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from(2 * INITIAL_DEPOSIT_AMOUNT / 3),
-            )],
+            &[
+                (
+                    &MOCK_CONTRACT_ADDR.to_string(),
+                    &Uint128::from(INITIAL_DEPOSIT_AMOUNT / 3),
+                ),
+                (
+                    &"addr0000".to_string(),
+                    &Uint128::from(INITIAL_DEPOSIT_AMOUNT / 3),
+                ),
+            ],
         ),
     ]);
 
@@ -199,6 +227,7 @@ fn test_query_dashboard() {
                     cust_avg_balance,
                     current_profit,
                     total_profit,
+                    total_insurance: Uint256::zero(),
                 }
             );
         }
@@ -226,7 +255,7 @@ fn test_query_config() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -307,6 +336,7 @@ fn test_query_config() {
                 bin.unwrap(),
                 ConfigResponse {
                     owner_addr: owner_addr,
+                    guardian_addr: "owner".to_string(),
                     market_contract: market_contract,
                     aterra_contract: aterra_contract,
                     cterra_contract: cterra_contract,
@@ -314,7 +344,17 @@ fn test_query_config() {
                     capa_contract: capa_contract,
                     insurance_contract: insurance_contract,
                     stable_denom: "uusd".to_string(),
-                    capa_yield: "100".to_string(),
+                    capa_yield: Decimal256::one(),
+                    insurance_share: Decimal256::zero(),
+                    min_deposit: Uint256::from(1_000_000u128),
+                    min_withdraw: Uint256::from(1_000_000u128),
+                    min_distribute_profit: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+                    deposit_paused: false,
+                    redeem_paused: false,
+                    distribute_paused: false,
+                    keeper_enabled: false,
+                    keeper_interval: DEFAULT_KEEPER_INTERVAL,
+                    keeper_bounty: Decimal256::zero(),
                 }
             );
         }
@@ -342,7 +382,7 @@ fn test_query_corpaccount() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -406,7 +446,7 @@ fn test_query_availableharvest() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -468,7 +508,7 @@ fn test_query_harvestedsum() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -530,7 +570,7 @@ fn test_query_token() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -593,7 +633,7 @@ fn test_query_cust_supply() {
     let msg = InstantiateMsg {
         owner_addr: String::from("owner"),
         stable_denom: "uusd".to_string(),
-        capa_yield: "100".to_string(),
+        capa_yield: Decimal256::one(),
     };
 
     let info = mock_info(
@@ -640,3 +680,246 @@ fn test_query_cust_supply() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+#[test]
+fn test_query_rate_history() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = get_register_contracts(&deps, &mock_config);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // deposits in the same period share a snapshot
+    let start_time = mock_env().block.time.seconds();
+    let mut env = mock_env();
+    for _ in 0..2 {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(10_000_000u128),
+            }],
+        );
+        let msg = ExecuteMsg::Deposit { recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+    }
+
+    let msg = QueryMsg::RealizedApy {
+        window_seconds: 86400,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            StdError::generic_err("Not enough rate snapshots in the window"),
+            msg
+        ),
+    }
+
+    let end_time = start_time + 15_768_000;
+    store_rate_snapshot(
+        &mut deps.storage,
+        end_time / RATE_SNAPSHOT_PERIOD,
+        &RateSnapshot {
+            time: end_time,
+            aterra_rate: Decimal256::percent(105),
+            capa_rate: Decimal256::percent(104),
+        },
+    )
+    .unwrap();
+
+    let msg = QueryMsg::RateHistory {
+        start_after: None,
+        limit: None,
+    };
+    let res: RateHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.snapshots,
+        vec![
+            RateSnapshotResponse {
+                time: start_time,
+                aterra_rate: Decimal256::one(),
+                capa_rate: Decimal256::one(),
+            },
+            RateSnapshotResponse {
+                time: end_time,
+                aterra_rate: Decimal256::percent(105),
+                capa_rate: Decimal256::percent(104),
+            },
+        ]
+    );
+
+    let msg = QueryMsg::RateHistory {
+        start_after: Some(start_time),
+        limit: Some(1),
+    };
+    let res: RateHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.snapshots.len(), 1);
+    assert_eq!(res.snapshots[0].time, end_time);
+
+    // half a year of growth, annualized
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(15_768_000);
+    let msg = QueryMsg::RealizedApy {
+        window_seconds: 15_768_000,
+    };
+    let res: RealizedApyResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        RealizedApyResponse {
+            start_time,
+            end_time,
+            aterra_apy: Decimal256::percent(10),
+            capa_apy: Decimal256::percent(8),
+        }
+    );
+}
+
+#[test]
+fn test_query_projections() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = get_register_contracts(&deps, &mock_config);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"cterra_contract".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1_000_000_000u128))],
+    )]);
+
+    // the aUST curve grows by 20% a year
+    let msg = QueryMsg::ProjectBalance {
+        address: String::from("addr0000"),
+        days: 365,
+    };
+    let res: ProjectionResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.cust_amount, Uint256::from(1_000_000_000u128));
+    assert_eq!(res.current_value, Uint256::from(1_000_000_000u128));
+    // 20% up to the rounding of inverting the aUST curve
+    assert_eq!(
+        res.projected_exchange_rate,
+        Decimal256::from_ratio(120_000_000_000_006_008u64, 100_000_000_000_000_000u64)
+    );
+    assert_eq!(res.projected_value, res.cust_amount * res.projected_exchange_rate);
+    assert_eq!(res.projected_interest, res.projected_value - res.current_value);
+
+    let msg = QueryMsg::ProjectDeposit {
+        amount: Uint256::from(1_000_000_000u128),
+        days: 365,
+    };
+    let deposit: ProjectionResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(deposit, res);
+
+    let msg = QueryMsg::ProjectDeposit {
+        amount: Uint256::from(1_000_000_000u128),
+        days: 3651,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(
+            StdError::generic_err("Projection limited to 3650 days"),
+            msg
+        ),
+    }
+
+    let msg = QueryMsg::ImpliedApy {};
+    let res: ImpliedApyResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.aterra_apy,
+        Decimal256::from_ratio(20_000_000_000_006_008u64, 100_000_000_000_000_000u64)
+    );
+    assert_eq!(res.capa_apy, res.aterra_apy);
+}
+
+#[test]
+fn test_query_account_summary() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+    let mock_config = get_mock_config(&deps);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+        capa_yield: Decimal256::one(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = get_register_contracts(&deps, &mock_config);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let account = deps.api.addr_canonicalize("addr0000").unwrap();
+    store_total_deposit(&mut deps.storage, &account, &Uint256::from(900_000_000u128)).unwrap();
+    store_last_ops_ust(&mut deps.storage, &account, &Uint256::from(900_000_000u128)).unwrap();
+    store_total_claim(&mut deps.storage, &account, &Uint256::from(25_000_000u128)).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"cterra_contract".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1_000_000_000u128))],
+    )]);
+
+    let msg = QueryMsg::AccountSummary {
+        address: String::from("addr0000"),
+    };
+    let res: AccountSummaryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        AccountSummaryResponse {
+            cust_balance: Uint256::from(1_000_000_000u128),
+            ust_value: Uint256::from(1_000_000_000u128),
+            total_deposit: Uint256::from(900_000_000u128),
+            last_ops_ust: Uint256::from(900_000_000u128),
+            available_harvest: Uint256::from(100_000_000u128),
+            harvested_sum: Uint256::from(25_000_000u128),
+            exchange_rate: Decimal256::one(),
+            capa_exchange_rate: Decimal256::one(),
+        }
+    );
+}
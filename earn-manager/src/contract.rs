@@ -1,13 +1,17 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, PendingOwnerResponse, QueryMsg};
 
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_config, read_pending_owner, remove_pending_owner, store_config, store_pending_owner,
+    Config, PendingOwner,
+};
 use cosmwasm_std::{
-      Binary, CanonicalAddr, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult, Uint128, 
+    attr, to_binary, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
 };
+use cw20::Expiration;
 
 pub const _1M_: u128 = 1000000;
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 100 * _1M_;
@@ -63,12 +67,21 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::RegisterContracts { earn55, earn100 } => {
             register_contracts(deps, info, &earn55, &earn100)
         },
+        ExecuteMsg::ProposeNewOwner {
+            owner_addr,
+            expires,
+        } => propose_new_owner(deps, env, info, owner_addr, expires),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    Ok(Binary::default())
+    match msg {
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        _ => Ok(Binary::default()),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -103,3 +116,91 @@ pub fn register_contracts(
 
     Ok(Response::default())
 }
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner_addr: String,
+    expires: Option<Expiration>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Ownership proposal expired"));
+    }
+
+    let new_owner = deps.api.addr_validate(&owner_addr)?;
+    store_pending_owner(
+        deps.storage,
+        &PendingOwner {
+            owner_addr: deps.api.addr_canonicalize(new_owner.as_str())?,
+            expires,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner_addr", new_owner),
+        attr("expires", expires.to_string()),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    if read_pending_owner(deps.storage)?.is_none() {
+        return Err(StdError::generic_err("No ownership proposal pending"));
+    }
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_proposal"))
+}
+
+pub fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    let pending_owner = match read_pending_owner(deps.storage)? {
+        Some(p) => p,
+        None => return Err(StdError::generic_err("No ownership proposal pending")),
+    };
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != pending_owner.owner_addr {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    if pending_owner.expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Ownership proposal expired"));
+    }
+
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner_addr = pending_owner.owner_addr;
+    store_config(deps.storage, &config)?;
+    remove_pending_owner(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner_addr", info.sender),
+    ]))
+}
+
+pub fn query_pending_owner(deps: Deps) -> StdResult<Option<PendingOwnerResponse>> {
+    let pending_owner = match read_pending_owner(deps.storage)? {
+        Some(p) => Some(PendingOwnerResponse {
+            owner_addr: deps.api.addr_humanize(&p.owner_addr)?.to_string(),
+            expires: p.expires,
+        }),
+        None => None,
+    };
+    Ok(pending_owner)
+}
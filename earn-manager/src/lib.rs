@@ -1,3 +1,6 @@
 pub mod contract;
 pub mod msg;
 pub mod state;
+
+#[cfg(test)]
+mod testing;
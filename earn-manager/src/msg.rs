@@ -1,4 +1,5 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cw20::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +22,15 @@ pub enum ExecuteMsg {
         earn55: String,
         earn100: String,
     },
+    /// Propose a new owner, who has to accept before the optional expiry
+    ProposeNewOwner {
+        owner_addr: String,
+        expires: Option<Expiration>,
+    },
+    /// Withdraw the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Accept a pending ownership proposal, only callable by the proposed owner
+    AcceptOwnership {},
     // Update config values
    // UpdateConfig {
    //     owner_addr: Option<Addr>,
@@ -34,6 +44,7 @@ pub enum QueryMsg {
     QueryContract {
         capa_yield: String,
     },
+    PendingOwner {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -61,6 +72,13 @@ pub struct ConfigResponse {
     pub stable_denom: String,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub owner_addr: String,
+    pub expires: Expiration,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MarketStateResponse {
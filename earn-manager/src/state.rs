@@ -4,9 +4,11 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{Addr, CanonicalAddr, Order, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cw20::Expiration;
 use cw_storage_plus::Map;
 
 pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_PENDING_OWNER: &[u8] = b"pending_owner";
 pub const KEY_BALANCE: &[u8] = b"balance";
 const PREFIX_PROFIT: &[u8] = b"profit";
 const PREFIX_TOTAL_DEPOSIT: &[u8] = b"td_";
@@ -31,6 +33,24 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner_addr: CanonicalAddr,
+    pub expires: Expiration,
+}
+
+pub fn store_pending_owner(storage: &mut dyn Storage, data: &PendingOwner) -> StdResult<()> {
+    Singleton::new(storage, KEY_PENDING_OWNER).save(data)
+}
+
+pub fn read_pending_owner(storage: &dyn Storage) -> StdResult<Option<PendingOwner>> {
+    ReadonlySingleton::new(storage, KEY_PENDING_OWNER).may_load()
+}
+
+pub fn remove_pending_owner(storage: &mut dyn Storage) {
+    Singleton::<PendingOwner>::new(storage, KEY_PENDING_OWNER).remove()
+}
+
 pub fn store_profit(storage: &mut dyn Storage, profit: &Uint256) -> StdResult<()> {
     Singleton::new(storage, PREFIX_PROFIT).save(profit)
 }
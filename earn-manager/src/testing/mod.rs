@@ -0,0 +1 @@
+mod ownership_ut;
@@ -0,0 +1,179 @@
+use crate::contract::{execute, instantiate, query, INITIAL_DEPOSIT_AMOUNT};
+use crate::msg::{ExecuteMsg, InstantiateMsg, PendingOwnerResponse, QueryMsg};
+use crate::state::read_config;
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{from_binary, Api, Coin, OwnedDeps, StdError, Uint128};
+use cw20::Expiration;
+
+fn instantiate_manager() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner_addr: String::from("owner"),
+        stable_denom: "uusd".to_string(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps
+}
+
+#[test]
+fn propose_new_owner_unauthorized() {
+    let mut deps = instantiate_manager();
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("Unauthorized"), msg),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: Option<PendingOwnerResponse> = from_binary(&res).unwrap();
+    assert_eq!(pending, None);
+}
+
+#[test]
+fn two_step_ownership_transfer() {
+    let mut deps = instantiate_manager();
+
+    // nothing to accept yet
+    let info = mock_info("owner2", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("No ownership proposal pending"), msg),
+    }
+
+    let expires = Expiration::AtHeight(mock_env().block.height + 10);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: Some(expires),
+    };
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: Option<PendingOwnerResponse> = from_binary(&res).unwrap();
+    assert_eq!(
+        pending,
+        Some(PendingOwnerResponse {
+            owner_addr: String::from("owner2"),
+            expires,
+        })
+    );
+
+    // only the proposed owner can accept
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("Unauthorized"), msg),
+    }
+
+    // the proposal cannot be accepted once expired
+    let mut env = mock_env();
+    env.block.height += 10;
+    let info = mock_info("owner2", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info.clone(),
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("Ownership proposal expired"), msg),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config.owner_addr,
+        deps.api.addr_canonicalize("owner2").unwrap()
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: Option<PendingOwnerResponse> = from_binary(&res).unwrap();
+    assert_eq!(pending, None);
+}
+
+#[test]
+fn cancel_ownership_proposal() {
+    let mut deps = instantiate_manager();
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner_addr: String::from("owner2"),
+        expires: None,
+    };
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // only the owner can cancel
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("Unauthorized"), msg),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+
+    let info = mock_info("owner2", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Ok(_msg) => panic!("DO NOT ENTER HERE"),
+        Err(msg) => assert_eq!(StdError::generic_err("No ownership proposal pending"), msg),
+    }
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config.owner_addr,
+        deps.api.addr_canonicalize("owner").unwrap()
+    );
+}